@@ -0,0 +1,259 @@
+use colors;
+use theme::{ColorGroup, Theme};
+
+pub const RESET: &str = "\x1b[0m";
+
+// What i3 uses for the colors a theme leaves out.
+const WINDOW_DEFAULTS: &[(&str, [&str; 4])] = &[
+    ("focused", ["#4c7899", "#285577", "#ffffff", "#2e9ef4"]),
+    ("focused_inactive", ["#333333", "#5f676a", "#ffffff", "#484e50"]),
+    ("unfocused", ["#333333", "#222222", "#888888", "#292d2e"]),
+    ("urgent", ["#2f343a", "#900000", "#ffffff", "#900000"]),
+];
+const WORKSPACE_DEFAULTS: &[(&str, [&str; 3])] = &[
+    ("focused_workspace", ["#4c7899", "#285577", "#ffffff"]),
+    ("active_workspace", ["#333333", "#5f676a", "#ffffff"]),
+    ("inactive_workspace", ["#333333", "#222222", "#888888"]),
+    ("urgent_workspace", ["#2f343a", "#900000", "#ffffff"]),
+];
+const BAR_DEFAULTS: [&str; 3] = ["#000000", "#ffffff", "#666666"];
+
+fn escape(layer: u8, color: &str) -> String {
+    match colors::parse_hex(color) {
+        Ok(c) => format!("\x1b[{};2;{};{};{}m", layer, c.red, c.green, c.blue),
+        Err(_) => String::new(),
+    }
+}
+
+pub fn fg(color: &str) -> String {
+    escape(38, color)
+}
+
+pub fn bg(color: &str) -> String {
+    escape(48, color)
+}
+
+// The colors of a window or workspace button with the defaults filled in.
+pub struct Colors {
+    pub border: String,
+    pub background: String,
+    pub text: String,
+    pub indicator: String,
+}
+
+impl Colors {
+    fn resolve(group: &Option<ColorGroup>, defaults: &[&str]) -> Colors {
+        let part = |value: Option<&String>, i: usize| {
+            value.cloned().unwrap_or_else(|| defaults[i.min(defaults.len() - 1)].to_string())
+        };
+        let group = group.as_ref();
+        Colors {
+            border: part(group.and_then(|g| g.border.as_ref()), 0),
+            background: part(group.and_then(|g| g.background.as_ref()), 1),
+            text: part(group.and_then(|g| g.text.as_ref()), 2),
+            indicator: part(group.and_then(|g| g.indicator.as_ref()), 3),
+        }
+    }
+}
+
+pub fn window_colors(theme: &Theme) -> Vec<(&'static str, Colors)> {
+    WINDOW_DEFAULTS
+        .iter()
+        .map(|&(name, ref defaults)| {
+            let group = match theme.window_colors {
+                Some(ref wc) => match name {
+                    "focused" => &wc.focused,
+                    "focused_inactive" => &wc.focused_inactive,
+                    "unfocused" => &wc.unfocused,
+                    _ => &wc.urgent,
+                },
+                None => &None,
+            };
+            (name, Colors::resolve(group, defaults))
+        })
+        .collect()
+}
+
+pub fn workspace_colors(theme: &Theme) -> Vec<(&'static str, Colors)> {
+    WORKSPACE_DEFAULTS
+        .iter()
+        .map(|&(name, ref defaults)| {
+            let group = match theme.bar_colors {
+                Some(ref bc) => match name {
+                    "focused_workspace" => &bc.focused_workspace,
+                    "active_workspace" => &bc.active_workspace,
+                    "inactive_workspace" => &bc.inactive_workspace,
+                    _ => &bc.urgent_workspace,
+                },
+                None => &None,
+            };
+            (name, Colors::resolve(group, defaults))
+        })
+        .collect()
+}
+
+// The background, statusline and separator colors of the bar.
+pub fn bar_colors(theme: &Theme) -> [String; 3] {
+    let get = |value: Option<&String>, i: usize| {
+        value.cloned().unwrap_or_else(|| BAR_DEFAULTS[i].to_string())
+    };
+    let bc = theme.bar_colors.as_ref();
+    [
+        get(bc.and_then(|b| b.background.as_ref()), 0),
+        get(bc.and_then(|b| b.statusline.as_ref()), 1),
+        get(bc.and_then(|b| b.separator.as_ref()), 2),
+    ]
+}
+
+fn fit(text: &str, width: usize) -> String {
+    let mut text: String = text.chars().take(width).collect();
+    while text.chars().count() < width {
+        text.push(' ');
+    }
+    text
+}
+
+// A title bar `width` columns wide with a column of border on both sides.
+pub fn title_bar(colors: &Colors, title: &str, width: usize) -> String {
+    format!(
+        "{} {}{} {}{} {}",
+        bg(&colors.border),
+        bg(&colors.background),
+        fg(&colors.text),
+        fit(title, width.saturating_sub(3)),
+        bg(&colors.border),
+        RESET
+    )
+}
+
+// A bar `width` columns wide with a button for each kind of workspace and
+// some status text on the right.
+pub fn bar(theme: &Theme, width: usize) -> String {
+    let [background, statusline, separator] = bar_colors(theme);
+    let mut line = bg(&background);
+    let mut used = 0;
+
+    for (i, (_, colors)) in workspace_colors(theme).iter().enumerate() {
+        if used + 5 > width {
+            break;
+        }
+        line.push_str(&format!(
+            "{}{}▌{} {} {}▐",
+            fg(&colors.border),
+            bg(&colors.background),
+            fg(&colors.text),
+            i + 1,
+            fg(&colors.border)
+        ));
+        line.push_str(&bg(&background));
+        used += 5;
+    }
+
+    let status = ["100%", "12:00"];
+    let status_width = status.iter().map(|s| s.len() + 3).sum::<usize>() + 1;
+    if used + status_width <= width {
+        line.push_str(&" ".repeat(width - used - status_width));
+        for (i, s) in status.iter().enumerate() {
+            if i > 0 {
+                line.push_str(&format!(" {}|", fg(&separator)));
+            } else {
+                line.push_str("  ");
+            }
+            line.push_str(&format!("{} {}", fg(&statusline), s));
+        }
+        line.push(' ');
+    } else {
+        line.push_str(&" ".repeat(width - used));
+    }
+
+    line.push_str(RESET);
+    line
+}
+
+// A few lines showing the window and bar colors of a theme.
+pub fn swatch(theme: &Theme, width: usize) -> Vec<String> {
+    let mut lines: Vec<String> = window_colors(theme)
+        .iter()
+        .map(|(name, colors)| {
+            format!(
+                "{}{}  {}",
+                title_bar(colors, name, width.saturating_sub(2)),
+                bg(&colors.indicator),
+                RESET
+            )
+        })
+        .collect();
+    lines.push(String::new());
+    lines.push(bar(theme, width));
+    lines
+}
+
+// A mock screen with a window for each state side by side and a bar below.
+// The indicator is drawn on the right edge, where i3 would open the next
+// window in a horizontal split.
+pub fn screen(theme: &Theme, width: usize) -> Vec<String> {
+    let windows = window_colors(theme);
+    let window_width = (width / windows.len()).max(6);
+    let body_height = 3;
+
+    let mut lines = vec![String::new(); body_height + 2];
+    for (name, colors) in &windows {
+        lines[0].push_str(&title_bar(colors, name, window_width));
+        for line in lines.iter_mut().skip(1).take(body_height) {
+            line.push_str(&format!(
+                "{} {}{}{} {}",
+                bg(&colors.border),
+                RESET,
+                " ".repeat(window_width - 2),
+                bg(&colors.indicator),
+                RESET
+            ));
+        }
+        lines[body_height + 1].push_str(&format!(
+            "{}{}{} {}",
+            bg(&colors.border),
+            " ".repeat(window_width - 1),
+            bg(&colors.indicator),
+            RESET
+        ));
+    }
+
+    lines.push(bar(theme, window_width * windows.len()));
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    extern crate regex;
+
+    use self::regex::Regex;
+    use theme::from_yaml;
+    use yaml_rust::YamlLoader;
+
+    #[test]
+    fn test_screen() {
+        let docs = YamlLoader::load_from_str(
+            "window_colors:
+  focused:
+    border: '#111111'
+    background: '#222222'
+    text: '#333333'
+",
+        )
+        .unwrap();
+        let theme = from_yaml(&docs[0]).unwrap();
+        let escapes = Regex::new("\x1b\\[[0-9;]*m").unwrap();
+
+        let lines = screen(&theme, 80);
+        assert_eq!(lines.len(), 6);
+        for line in &lines {
+            assert_eq!(escapes.replace_all(line, "").chars().count(), 80);
+        }
+
+        // the parts the theme leaves out are drawn in the colors i3 would use
+        assert!(lines[0].starts_with("\x1b[48;2;17;17;17m \x1b[48;2;34;34;34m\x1b[38;2;51;51;51m focused"));
+        assert!(lines[1].contains(&bg("#2e9ef4")));
+    }
+}
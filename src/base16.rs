@@ -0,0 +1,210 @@
+extern crate linked_hash_map;
+extern crate yaml_rust;
+
+use self::linked_hash_map::LinkedHashMap;
+use yaml_rust::Yaml;
+
+const BASES: &[&str] = &[
+    "base00", "base01", "base02", "base03", "base04", "base05", "base06", "base07", "base08",
+    "base09", "base0A", "base0B", "base0C", "base0D", "base0E", "base0F",
+];
+
+// The roles from the base16 template for i3 (border, background, text and
+// indicator).
+const WINDOW_COLORS: &[(&str, [&str; 4])] = &[
+    ("focused", ["base05", "base0D", "base00", "base0D"]),
+    ("focused_inactive", ["base01", "base01", "base05", "base03"]),
+    ("unfocused", ["base01", "base00", "base05", "base01"]),
+    ("urgent", ["base08", "base08", "base00", "base08"]),
+];
+const BAR_COLORS: &[(&str, &str)] = &[
+    ("background", "base00"),
+    ("separator", "base01"),
+    ("statusline", "base04"),
+];
+const WORKSPACE_COLORS: &[(&str, [&str; 3])] = &[
+    ("focused_workspace", ["base05", "base0D", "base00"]),
+    ("active_workspace", ["base05", "base03", "base00"]),
+    ("inactive_workspace", ["base03", "base01", "base05"]),
+    ("urgent_workspace", ["base08", "base08", "base00"]),
+];
+const PARTS: &[&str] = &["border", "background", "text", "indicator"];
+
+fn s(value: &str) -> Yaml {
+    Yaml::String(value.to_string())
+}
+
+// Schemes have their colors either at the top level or, in the newer
+// format, under `palette`. The keys are sometimes lowercase.
+fn scheme_colors(doc: &Yaml) -> &Yaml {
+    if doc["palette"].as_hash().is_some() {
+        &doc["palette"]
+    } else {
+        doc
+    }
+}
+
+fn base<'a>(colors: &'a Yaml, name: &str) -> Option<&'a str> {
+    colors[name]
+        .as_str()
+        .or_else(|| colors[name.to_lowercase().as_str()].as_str())
+}
+
+pub fn is_scheme(doc: &Yaml) -> bool {
+    let colors = scheme_colors(doc);
+    BASES.iter().all(|b| base(colors, b).is_some())
+        && doc["window_colors"].is_badvalue()
+        && doc["bar_colors"].is_badvalue()
+}
+
+fn group(parts: &[&str]) -> Yaml {
+    let mut group = LinkedHashMap::new();
+    for (part, color) in PARTS.iter().zip(parts) {
+        group.insert(s(part), s(color));
+    }
+    Yaml::Hash(group)
+}
+
+// Turns a base16 scheme into an i3-style theme. The base colors are kept
+// in `colors` under their names, so the result is easy to tweak.
+pub fn to_theme_doc(doc: &Yaml) -> Result<Yaml, String> {
+    let colors = scheme_colors(doc);
+
+    let mut meta = LinkedHashMap::new();
+    let name = doc["scheme"].as_str().or_else(|| doc["name"].as_str());
+    if let Some(name) = name {
+        meta.insert(s("description"), s(&format!("{} (base16)", name)));
+    }
+    if let Some(author) = doc["author"].as_str() {
+        meta.insert(s("author"), s(author));
+    }
+    if let Some(variant) = doc["variant"].as_str() {
+        meta.insert(s("variant"), s(variant));
+    }
+    meta.insert(s("tags"), Yaml::Array(vec![s("base16")]));
+
+    let mut palette = LinkedHashMap::new();
+    for b in BASES {
+        let value = match base(colors, b) {
+            Some(v) => v.trim(),
+            None => return Err(format!("base16 scheme is missing {}", b)),
+        };
+        let value = if value.starts_with('#') {
+            value.to_string()
+        } else {
+            format!("#{}", value)
+        };
+        palette.insert(s(b), s(&value));
+    }
+
+    let mut window_colors = LinkedHashMap::new();
+    for (name, parts) in WINDOW_COLORS {
+        window_colors.insert(s(name), group(parts));
+    }
+
+    let mut bar_colors = LinkedHashMap::new();
+    for (name, color) in BAR_COLORS {
+        bar_colors.insert(s(name), s(color));
+    }
+    for (name, parts) in WORKSPACE_COLORS {
+        bar_colors.insert(s(name), group(parts));
+    }
+
+    let mut theme = LinkedHashMap::new();
+    theme.insert(s("meta"), Yaml::Hash(meta));
+    theme.insert(s("colors"), Yaml::Hash(palette));
+    theme.insert(s("window_colors"), Yaml::Hash(window_colors));
+    theme.insert(s("bar_colors"), Yaml::Hash(bar_colors));
+    Ok(Yaml::Hash(theme))
+}
+
+// Lets a base16 scheme be used anywhere a theme can.
+pub fn convert_if_scheme(doc: Yaml) -> Result<Yaml, String> {
+    if is_scheme(&doc) {
+        to_theme_doc(&doc)
+    } else {
+        Ok(doc)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use theme::{from_yaml, Variant};
+    use yaml_rust::YamlLoader;
+
+    #[test]
+    fn test_base16() {
+        let old = YamlLoader::load_from_str(
+            "scheme: \"Default Dark\"
+author: \"Chris Kempson (http://chriskempson.com)\"
+base00: \"181818\"
+base01: \"282828\"
+base02: \"383838\"
+base03: \"585858\"
+base04: \"b8b8b8\"
+base05: \"d8d8d8\"
+base06: \"e8e8e8\"
+base07: \"f8f8f8\"
+base08: \"ab4642\"
+base09: \"dc9656\"
+base0A: \"f7ca88\"
+base0B: \"a1b56c\"
+base0C: \"86c1b9\"
+base0D: \"7cafc2\"
+base0E: \"ba8baf\"
+base0F: \"a16946\"
+",
+        )
+        .unwrap()
+        .remove(0);
+        let new = YamlLoader::load_from_str(
+            "system: base16
+name: Default Light
+author: Chris Kempson
+variant: light
+palette:
+  base00: '#f8f8f8'
+  base01: '#e8e8e8'
+  base02: '#d8d8d8'
+  base03: '#b8b8b8'
+  base04: '#585858'
+  base05: '#383838'
+  base06: '#282828'
+  base07: '#181818'
+  base08: '#ab4642'
+  base09: '#dc9656'
+  base0a: '#f7ca88'
+  base0b: '#a1b56c'
+  base0c: '#86c1b9'
+  base0d: '#7cafc2'
+  base0e: '#ba8baf'
+  base0f: '#a16946'
+",
+        )
+        .unwrap()
+        .remove(0);
+
+        assert!(is_scheme(&old));
+        assert!(is_scheme(&new));
+
+        let theme = from_yaml(&convert_if_scheme(old).unwrap()).unwrap();
+        assert_eq!(theme.meta.description, Some("Default Dark (base16)".to_string()));
+        let focused = theme.window_colors.unwrap().focused.unwrap();
+        assert_eq!(focused.border, Some("#d8d8d8".to_string()));
+        assert_eq!(focused.background, Some("#7cafc2".to_string()));
+        assert_eq!(focused.text, Some("#181818".to_string()));
+
+        let theme = from_yaml(&convert_if_scheme(new).unwrap()).unwrap();
+        assert_eq!(theme.meta.variant, Some(Variant::Light));
+        let bar = theme.bar_colors.unwrap();
+        assert_eq!(bar.background, Some("#f8f8f8".to_string()));
+        assert_eq!(bar.urgent_workspace.unwrap().background, Some("#ab4642".to_string()));
+
+        let solarized = YamlLoader::load_from_str(include_str!("../themes/solarized"))
+            .unwrap()
+            .remove(0);
+        assert!(!is_scheme(&solarized));
+    }
+}
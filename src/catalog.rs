@@ -0,0 +1,331 @@
+extern crate flate2;
+extern crate tar;
+extern crate yaml_rust;
+
+use self::flate2::read::GzDecoder;
+use std::env;
+use std::ffi::OsString;
+use std::fs;
+use std::fs::File;
+use std::io::prelude::*;
+use std::path::{Path, PathBuf};
+use base16;
+use format;
+use theme;
+
+// Where a theme listed by `--list-all` was found.
+pub enum ThemeSource {
+    Embedded,
+    User(PathBuf),
+}
+
+fn split_paths(paths: Option<OsString>) -> Vec<PathBuf> {
+    match paths {
+        Some(paths) => env::split_paths(&paths)
+            .filter(|p| p.is_absolute())
+            .collect(),
+        None => Vec::new(),
+    }
+}
+
+fn theme_dirs_from(
+    i3_style_path: Option<OsString>,
+    data_home: Option<OsString>,
+    data_dirs: Option<OsString>,
+    home: Option<OsString>,
+) -> Vec<PathBuf> {
+    let mut dirs = split_paths(i3_style_path);
+
+    // defaults from the XDG base directory specification
+    let data_home = match split_paths(data_home).into_iter().next() {
+        Some(d) => Some(d),
+        None => home.map(|h| PathBuf::from(h).join(".local/share")),
+    };
+    let mut data_dirs = split_paths(data_dirs);
+    if data_dirs.is_empty() {
+        data_dirs = vec![
+            PathBuf::from("/usr/local/share"),
+            PathBuf::from("/usr/share"),
+        ];
+    }
+
+    for dir in data_home.into_iter().chain(data_dirs) {
+        dirs.push(dir.join("i3-style").join("themes"));
+    }
+
+    dirs
+}
+
+// The user theme directories in the order they are searched. Themes found in
+// earlier directories shadow themes of the same name in later ones, and all of
+// them shadow the embedded themes.
+pub fn theme_dirs() -> Vec<PathBuf> {
+    theme_dirs_from(
+        env::var_os("I3_STYLE_PATH"),
+        env::var_os("XDG_DATA_HOME"),
+        env::var_os("XDG_DATA_DIRS"),
+        env::var_os("HOME"),
+    )
+}
+
+// The directory `install` copies themes to, which is the first one under the
+// XDG data directories.
+pub fn user_theme_dir() -> Option<PathBuf> {
+    theme_dirs_from(
+        None,
+        env::var_os("XDG_DATA_HOME"),
+        None,
+        env::var_os("HOME"),
+    )
+    .into_iter()
+    .next()
+}
+
+fn theme_name(path: &Path) -> Option<String> {
+    let file_name = path.file_name()?.to_str()?;
+    if file_name.starts_with('.') {
+        return None;
+    }
+    for extension in format::EXTENSIONS {
+        if let Some(name) = file_name.strip_suffix(extension).and_then(|n| n.strip_suffix('.')) {
+            return Some(name.to_string());
+        }
+    }
+    Some(file_name.to_string())
+}
+
+// The files a theme called `name` can be in.
+fn theme_files(dir: &Path, name: &str) -> Vec<PathBuf> {
+    let mut files = vec![dir.join(name)];
+    files.extend(format::EXTENSIONS.iter().map(|e| dir.join(format!("{}.{}", name, e))));
+    files
+}
+
+fn find_theme_in(dirs: &[PathBuf], name: &str) -> Option<PathBuf> {
+    if name.contains('/') {
+        return None;
+    }
+
+    for dir in dirs {
+        for candidate in &theme_files(dir, name) {
+            if candidate.is_file() {
+                return Some(candidate.clone());
+            }
+        }
+    }
+
+    None
+}
+
+pub fn find_theme(name: &str) -> Option<PathBuf> {
+    find_theme_in(&theme_dirs(), name)
+}
+
+fn user_themes_in(dirs: &[PathBuf]) -> Vec<(String, PathBuf)> {
+    let mut themes: Vec<(String, PathBuf)> = Vec::new();
+
+    for dir in dirs {
+        let entries = match fs::read_dir(dir) {
+            Ok(entries) => entries,
+            Err(_) => continue,
+        };
+
+        let mut paths: Vec<PathBuf> = entries
+            .filter_map(|e| e.ok())
+            .map(|e| e.path())
+            .filter(|p| p.is_file())
+            .collect();
+        paths.sort();
+
+        for path in paths {
+            let name = match theme_name(&path) {
+                Some(n) => n,
+                None => continue,
+            };
+            if !themes.iter().any(|(n, _)| n == &name) {
+                themes.push((name, path));
+            }
+        }
+    }
+
+    themes
+}
+
+// Every theme in the user theme directories with the path it will be loaded
+// from, without the ones that are shadowed.
+pub fn user_themes() -> Vec<(String, PathBuf)> {
+    user_themes_in(&theme_dirs())
+}
+
+fn validate_theme(contents: &str) -> Result<(), String> {
+    let doc = format::parse(contents, format::guess(contents))?;
+    theme::from_yaml(&base16::convert_if_scheme(doc)?).map(|_| ())
+}
+
+fn read_archive(path: &Path) -> Result<Vec<(String, String)>, String> {
+    let file = File::open(path).map_err(|e| e.to_string())?;
+    let mut archive = tar::Archive::new(GzDecoder::new(file));
+    let mut themes = Vec::new();
+
+    for entry in archive.entries().map_err(|e| e.to_string())? {
+        let mut entry = entry.map_err(|e| e.to_string())?;
+        if !entry.header().entry_type().is_file() {
+            continue;
+        }
+        let name = match theme_name(&entry.path().map_err(|e| e.to_string())?) {
+            Some(n) => n,
+            None => continue,
+        };
+        let mut contents = String::new();
+        entry
+            .read_to_string(&mut contents)
+            .map_err(|e| format!("{}: {}", name, e))?;
+        themes.push((name, contents));
+    }
+
+    Ok(themes)
+}
+
+// Reads the themes to install from a theme file, a directory of theme files
+// or a gzipped tarball of them. Every theme is validated before anything is
+// installed.
+pub fn read_theme_sources(path: &Path) -> Result<Vec<(String, String)>, String> {
+    let mut themes = Vec::new();
+
+    if path.is_dir() {
+        for (name, file) in user_themes_in(&[path.to_path_buf()]) {
+            let contents = fs::read_to_string(&file).map_err(|e| format!("{}: {}", name, e))?;
+            themes.push((name, contents));
+        }
+    } else if path.to_string_lossy().ends_with(".tar.gz") || path.to_string_lossy().ends_with(".tgz") {
+        themes = read_archive(path)?;
+    } else {
+        let name = match theme_name(path) {
+            Some(n) => n,
+            None => return Err(format!("not a theme file: {}", path.display())),
+        };
+        let contents = fs::read_to_string(path).map_err(|e| e.to_string())?;
+        themes.push((name, contents));
+    }
+
+    if themes.is_empty() {
+        return Err(format!("no themes found in {}", path.display()));
+    }
+
+    for (name, contents) in &themes {
+        validate_theme(contents).map_err(|e| format!("{}: {}", name, e))?;
+    }
+
+    Ok(themes)
+}
+
+pub fn install_theme(dir: &Path, name: &str, contents: &str) -> Result<PathBuf, String> {
+    if name.is_empty() || name.contains('/') || name.starts_with('.') {
+        return Err(format!("invalid theme name: {}", name));
+    }
+
+    fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+
+    // don't leave a stale copy around under another file name
+    for file in theme_files(dir, name) {
+        if file.is_file() {
+            fs::remove_file(&file).map_err(|e| e.to_string())?;
+        }
+    }
+
+    let path = dir.join(format!("{}.{}", name, format::guess(contents)));
+    fs::write(&path, contents).map_err(|e| e.to_string())?;
+    Ok(path)
+}
+
+pub fn uninstall_theme(dir: &Path, name: &str) -> Result<PathBuf, String> {
+    match find_theme_in(&[dir.to_path_buf()], name) {
+        Some(path) => {
+            fs::remove_file(&path).map_err(|e| e.to_string())?;
+            Ok(path)
+        }
+        None => Err(format!("theme is not installed in {}: {}", dir.display(), name)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    extern crate tempfile;
+
+    use self::tempfile::tempdir;
+    use std::fs::File;
+
+    #[test]
+    fn test_theme_search_path() {
+        let dirs = theme_dirs_from(
+            Some(OsString::from("/opt/themes:relative/ignored")),
+            None,
+            Some(OsString::from("/usr/share")),
+            Some(OsString::from("/home/user")),
+        );
+        assert_eq!(
+            dirs,
+            vec![
+                PathBuf::from("/opt/themes"),
+                PathBuf::from("/home/user/.local/share/i3-style/themes"),
+                PathBuf::from("/usr/share/i3-style/themes"),
+            ]
+        );
+
+        let first = tempdir().unwrap();
+        let second = tempdir().unwrap();
+        File::create(first.path().join("solarized.yaml")).unwrap();
+        File::create(second.path().join("solarized")).unwrap();
+        File::create(second.path().join("mine")).unwrap();
+        File::create(second.path().join("ocean.toml")).unwrap();
+        File::create(second.path().join(".hidden")).unwrap();
+        let dirs = vec![first.path().to_path_buf(), second.path().to_path_buf()];
+
+        assert_eq!(
+            find_theme_in(&dirs, "solarized"),
+            Some(first.path().join("solarized.yaml"))
+        );
+        assert_eq!(find_theme_in(&dirs, "mine"), Some(second.path().join("mine")));
+        assert_eq!(find_theme_in(&dirs, "ocean"), Some(second.path().join("ocean.toml")));
+        assert_eq!(find_theme_in(&dirs, "missing"), None);
+
+        let themes = user_themes_in(&dirs);
+        assert_eq!(
+            themes,
+            vec![
+                ("solarized".to_string(), first.path().join("solarized.yaml")),
+                ("mine".to_string(), second.path().join("mine")),
+                ("ocean".to_string(), second.path().join("ocean.toml")),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_install_and_uninstall() {
+        let mut solarized = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        solarized.push("themes/solarized");
+
+        let source = tempdir().unwrap();
+        fs::copy(&solarized, source.path().join("mine.yaml")).unwrap();
+        fs::write(source.path().join("broken"), "colors: [").unwrap();
+
+        assert!(read_theme_sources(source.path()).is_err());
+        fs::remove_file(source.path().join("broken")).unwrap();
+
+        let themes = read_theme_sources(source.path()).unwrap();
+        assert_eq!(themes.len(), 1);
+        assert_eq!(themes[0].0, "mine");
+
+        let dir = tempdir().unwrap();
+        let target = dir.path().join("themes");
+        let path = install_theme(&target, "ours", &themes[0].1).unwrap();
+        assert_eq!(path, target.join("ours.yaml"));
+        assert_eq!(find_theme_in(std::slice::from_ref(&target), "ours"), Some(path.clone()));
+        assert!(install_theme(&target, "../escape", &themes[0].1).is_err());
+
+        assert_eq!(uninstall_theme(&target, "ours").unwrap(), path);
+        assert!(uninstall_theme(&target, "ours").is_err());
+    }
+}
@@ -0,0 +1,437 @@
+extern crate linked_hash_map;
+
+use linked_hash_map::LinkedHashMap;
+use palette::{named, FromColor, Hsl, Srgb, Srgba, WithAlpha};
+use std::str::FromStr;
+
+// A color as it appears in a theme file: a hex literal, a reference to another
+// entry of the `colors` map or a CSS color name, or a function of other colors.
+// CSS `rgb()` and `hsl()` notations are parsed as functions too.
+#[derive(Clone, Debug, PartialEq)]
+enum Expr {
+    Literal(String),
+    Ref(String),
+    Number(f32),
+    Percent(f32),
+    Call(String, Vec<Expr>),
+}
+
+impl Expr {
+    // Numbers and percentages as a fraction, e.g. `15%` and `0.15` are the same.
+    fn fraction(&self) -> Option<f32> {
+        match *self {
+            Expr::Number(n) | Expr::Percent(n) => Some(n),
+            _ => None,
+        }
+    }
+}
+
+struct Parser<'a> {
+    input: &'a str,
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn parse(input: &'a str) -> Result<Expr, String> {
+        let mut parser = Parser { input, pos: 0 };
+        let expr = parser.expr()?;
+        parser.skip_whitespace();
+        if parser.pos != input.len() {
+            return Err(format!("unexpected input at `{}`", &input[parser.pos..]));
+        }
+        Ok(expr)
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.input[self.pos..].chars().next()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(c) = self.peek().filter(|c| c.is_whitespace()) {
+            self.pos += c.len_utf8();
+        }
+    }
+
+    fn take_while(&mut self, pred: impl Fn(char) -> bool) -> &'a str {
+        let start = self.pos;
+        while let Some(c) = self.peek() {
+            if !pred(c) {
+                break;
+            }
+            self.pos += c.len_utf8();
+        }
+        &self.input[start..self.pos]
+    }
+
+    fn expr(&mut self) -> Result<Expr, String> {
+        self.skip_whitespace();
+        match self.peek() {
+            Some('#') => {
+                self.pos += 1;
+                let hex = self.take_while(|c| c.is_ascii_hexdigit());
+                Ok(Expr::Literal(format!("#{}", hex)))
+            }
+            Some(c) if c.is_ascii_digit() || c == '.' => {
+                let number = self.take_while(|c| c.is_ascii_digit() || c == '.');
+                let number: f32 = number
+                    .parse()
+                    .map_err(|_| format!("invalid number: {}", number))?;
+                if self.peek() == Some('%') {
+                    self.pos += 1;
+                    Ok(Expr::Percent(number / 100.0))
+                } else {
+                    // hues in css may carry a unit
+                    if self.input[self.pos..].starts_with("deg") {
+                        self.pos += 3;
+                    }
+                    Ok(Expr::Number(number))
+                }
+            }
+            Some(c) if c.is_alphabetic() || c == '_' => {
+                let name = self.take_while(|c| c.is_alphanumeric() || c == '_' || c == '-');
+                self.skip_whitespace();
+                if self.peek() != Some('(') {
+                    return Ok(Expr::Ref(name.to_string()));
+                }
+                self.pos += 1;
+                let mut args = Vec::new();
+                self.skip_whitespace();
+                if self.peek() == Some(')') {
+                    self.pos += 1;
+                } else {
+                    // arguments are separated by commas, or by spaces and a slash
+                    // before the alpha as in `hsl(205 69% 49% / 0.5)`
+                    loop {
+                        args.push(self.expr()?);
+                        self.skip_whitespace();
+                        match self.peek() {
+                            Some(',') | Some('/') => self.pos += 1,
+                            Some(')') => {
+                                self.pos += 1;
+                                break;
+                            }
+                            Some(_) => (),
+                            None => return Err("expected `)` but found end of input".to_string()),
+                        }
+                    }
+                }
+                Ok(Expr::Call(name.to_string(), args))
+            }
+            Some(c) => Err(format!("unexpected `{}`", c)),
+            None => Err("empty color".to_string()),
+        }
+    }
+}
+
+pub fn parse_hex(hex: &str) -> Result<Srgba<u8>, String> {
+    let digits = hex.strip_prefix('#').unwrap_or(hex);
+    match digits.len() {
+        3 | 6 => Srgb::<u8>::from_str(digits).map(|c| c.with_alpha(255)),
+        4 | 8 => Srgba::<u8>::from_str(digits),
+        _ => return Err(format!("not a hex color: {}", hex)),
+    }
+    .map_err(|_| format!("not a hex color: {}", hex))
+}
+
+// Evaluates a standalone color, e.g. one given on the command line.
+pub fn eval_color(spec: &str) -> Result<String, String> {
+    Palette::new(&LinkedHashMap::new())?.eval_spec(spec)
+}
+
+pub fn to_hex(color: Srgba<u8>) -> String {
+    if color.alpha == 255 {
+        format!("#{:02x}{:02x}{:02x}", color.red, color.green, color.blue)
+    } else {
+        format!(
+            "#{:02x}{:02x}{:02x}{:02x}",
+            color.red, color.green, color.blue, color.alpha
+        )
+    }
+}
+
+fn adjust_lightness(color: Srgba<u8>, amount: f32) -> Srgba<u8> {
+    let alpha = color.alpha;
+    let mut hsl = Hsl::from_color(color.color.into_format::<f32>());
+    hsl.lightness = (hsl.lightness + amount).clamp(0.0, 1.0);
+    Srgb::from_color(hsl).into_format::<u8>().with_alpha(alpha)
+}
+
+fn mix(a: Srgba<u8>, b: Srgba<u8>, weight: f32) -> Srgba<u8> {
+    let channel = |x: u8, y: u8| (x as f32 + (y as f32 - x as f32) * weight).round() as u8;
+    Srgba::new(
+        channel(a.red, b.red),
+        channel(a.green, b.green),
+        channel(a.blue, b.blue),
+        channel(a.alpha, b.alpha),
+    )
+}
+
+fn css_function(name: &str, args: &[Expr]) -> Result<Srgba<u8>, String> {
+    if args.len() != 3 && args.len() != 4 {
+        return Err(format!("{}() takes 3 or 4 arguments but got {}", name, args.len()));
+    }
+
+    let mut values = Vec::new();
+    for arg in args {
+        match *arg {
+            Expr::Number(_) | Expr::Percent(_) => values.push(arg),
+            _ => return Err(format!("the arguments of {}() must be numbers", name)),
+        }
+    }
+
+    let alpha = values.get(3).map_or(1.0, |a| a.fraction().unwrap());
+    let color: Srgb = if name.starts_with("rgb") {
+        let channel = |arg: &Expr| match *arg {
+            Expr::Percent(p) => p,
+            Expr::Number(n) => n / 255.0,
+            _ => unreachable!(),
+        };
+        Srgb::new(channel(values[0]), channel(values[1]), channel(values[2]))
+    } else {
+        // saturation and lightness written without `%` are percentages as well
+        let percent = |arg: &Expr| match *arg {
+            Expr::Percent(p) => p,
+            Expr::Number(n) => n / 100.0,
+            _ => unreachable!(),
+        };
+        let hue = values[0].fraction().unwrap();
+        Srgb::from_color(Hsl::new(hue, percent(values[1]), percent(values[2])))
+    };
+
+    let color = Srgb::new(
+        color.red.clamp(0.0, 1.0),
+        color.green.clamp(0.0, 1.0),
+        color.blue.clamp(0.0, 1.0),
+    );
+    Ok(color
+        .into_format::<u8>()
+        .with_alpha((alpha.clamp(0.0, 1.0) * 255.0).round() as u8))
+}
+
+// Resolves the `colors` map of a theme. Entries may refer to each other in any
+// order; each one is evaluated once, after everything it depends on.
+pub struct Palette {
+    exprs: LinkedHashMap<String, Expr>,
+    resolved: LinkedHashMap<String, String>,
+    stack: Vec<String>,
+}
+
+impl Palette {
+    pub fn new(colors: &LinkedHashMap<String, String>) -> Result<Palette, String> {
+        let mut exprs = LinkedHashMap::new();
+        for (key, value) in colors {
+            let expr = Parser::parse(value).map_err(|e| format!("color `{}`: {}", key, e))?;
+            exprs.insert(key.clone(), expr);
+        }
+
+        let mut palette = Palette {
+            exprs,
+            resolved: LinkedHashMap::new(),
+            stack: Vec::new(),
+        };
+
+        let names: Vec<String> = palette.exprs.keys().cloned().collect();
+        for name in &names {
+            palette.resolve(name)?;
+        }
+
+        // keep the order the colors were declared in rather than the order they were resolved in
+        let mut resolved = LinkedHashMap::new();
+        for name in &names {
+            resolved.insert(name.clone(), palette.resolved[name].clone());
+        }
+        palette.resolved = resolved;
+
+        Ok(palette)
+    }
+
+    // Evaluates a color spec from elsewhere in the theme against this palette.
+    pub fn eval_spec(&mut self, spec: &str) -> Result<String, String> {
+        let expr = Parser::parse(spec)?;
+        self.eval(&expr)
+    }
+
+    fn resolve(&mut self, name: &str) -> Result<String, String> {
+        if let Some(color) = self.resolved.get(name) {
+            return Ok(color.clone());
+        }
+
+        if let Some(start) = self.stack.iter().position(|n| n == name) {
+            let mut cycle = self.stack[start..].to_vec();
+            cycle.push(name.to_string());
+            return Err(format!("color cycle: {}", cycle.join(" -> ")));
+        }
+
+        let expr = match self.exprs.get(name) {
+            Some(expr) => expr.clone(),
+            None => return Err(format!("unknown color: {}", name)),
+        };
+
+        self.stack.push(name.to_string());
+        let result = self.eval(&expr);
+        self.stack.pop();
+
+        let color = result.map_err(|e| format!("color `{}`: {}", name, e))?;
+        self.resolved.insert(name.to_string(), color.clone());
+        Ok(color)
+    }
+
+    fn eval(&mut self, expr: &Expr) -> Result<String, String> {
+        match expr {
+            Expr::Literal(hex) => {
+                let color = parse_hex(hex)?;
+                // i3 only understands the long forms, so expand `#abc`
+                if hex.len() == 7 || hex.len() == 9 {
+                    Ok(hex.clone())
+                } else {
+                    Ok(to_hex(color))
+                }
+            }
+            Expr::Ref(name) => {
+                if !self.exprs.contains_key(name) {
+                    if let Some(color) = named::from_str(&name.to_lowercase()) {
+                        return Ok(to_hex(color.with_alpha(255)));
+                    }
+                }
+                self.resolve(name)
+            }
+            Expr::Number(n) | Expr::Percent(n) => {
+                Err(format!("expected a color but found the number {}", n))
+            }
+            Expr::Call(name, args) => self.call(name, args),
+        }
+    }
+
+    fn eval_color(&mut self, expr: &Expr) -> Result<Srgba<u8>, String> {
+        let hex = self.eval(expr)?;
+        parse_hex(&hex)
+    }
+
+    fn call(&mut self, name: &str, args: &[Expr]) -> Result<String, String> {
+        if ["rgb", "rgba", "hsl", "hsla"].contains(&name) {
+            return css_function(name, args).map(to_hex);
+        }
+
+        let arity = match name {
+            "darken" | "lighten" | "alpha" => 2,
+            "mix" => 3,
+            _ => return Err(format!("unknown function: {}", name)),
+        };
+        if args.len() != arity {
+            return Err(format!(
+                "{}() takes {} arguments but got {}",
+                name,
+                arity,
+                args.len()
+            ));
+        }
+
+        let number = match args[arity - 1].fraction() {
+            Some(n) => n,
+            None => return Err(format!("the last argument of {}() must be a number", name)),
+        };
+        // a bare `15` is most likely meant as 15% and not 1500%
+        if !(0.0..=1.0).contains(&number) {
+            return Err(format!(
+                "the last argument of {}() must be between 0 and 1 or 0% and 100%",
+                name
+            ));
+        }
+
+        let color = self.eval_color(&args[0])?;
+        let color = match name {
+            "darken" => adjust_lightness(color, -number),
+            "lighten" => adjust_lightness(color, number),
+            "alpha" => Srgba::new(
+                color.red,
+                color.green,
+                color.blue,
+                (number * 255.0).round() as u8,
+            ),
+            "mix" => {
+                let other = self.eval_color(&args[1])?;
+                mix(color, other, number)
+            }
+            _ => unreachable!(),
+        };
+
+        Ok(to_hex(color))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use format;
+    use theme::ThemeFile;
+
+    fn palette(yaml: &str) -> Result<Palette, String> {
+        let file: ThemeFile = format::from_doc(&format::parse(yaml, "yaml").unwrap())?;
+        Palette::new(&file.colors)
+    }
+
+    #[test]
+    fn test_color_expressions() {
+        let palette = palette(
+            "colors:
+  accent_dim: darken(accent, 15%)
+  accent: '#268bd2'
+  base02: '#073642'
+  green: '#859900'
+  blend: mix(base02, green, 0.3)
+  veil: alpha(base02, 0.8)
+  same: accent
+",
+        )
+        .unwrap();
+        let colors = &palette.resolved;
+
+        let names: Vec<&String> = colors.keys().collect();
+        assert_eq!(names[0], "accent_dim");
+        assert_eq!(colors["accent"], "#268bd2");
+        assert_eq!(colors["same"], "#268bd2");
+        assert_eq!(colors["accent_dim"], "#1a6091");
+        assert_eq!(colors["blend"], "#2d542e");
+        assert_eq!(colors["veil"], "#073642cc");
+    }
+
+    #[test]
+    fn test_css_notations() {
+        assert_eq!(eval_color("rgb(38,139,210)").unwrap(), "#268bd2");
+        assert_eq!(eval_color("rgb(100%, 0%, 0%)").unwrap(), "#ff0000");
+        assert_eq!(eval_color("rgba(38, 139, 210, 0.5)").unwrap(), "#268bd280");
+        assert_eq!(eval_color("hsl(205 69% 49%)").unwrap(), "#278bd3");
+        assert_eq!(eval_color("hsl(120deg 100% 50% / 50%)").unwrap(), "#00ff0080");
+        assert_eq!(eval_color("#abc").unwrap(), "#aabbcc");
+        assert_eq!(eval_color("#FDF6E3").unwrap(), "#FDF6E3");
+        assert_eq!(eval_color("rebeccapurple").unwrap(), "#663399");
+        assert_eq!(eval_color("RebeccaPurple").unwrap(), "#663399");
+        assert_eq!(eval_color("darken(white, 50%)").unwrap(), "#808080");
+        assert!(eval_color("notacolor").is_err());
+        assert!(eval_color("rgb(1, 2)").is_err());
+        assert_eq!(eval_color("rgb(38,\u{a0}139, 210)").unwrap(), "#268bd2");
+
+        // names in the palette take precedence over css names
+        let palette = palette("colors:\n  red: '#dc322f'\n  alarm: red\n").unwrap();
+        assert_eq!(palette.resolved["alarm"], "#dc322f");
+    }
+
+    #[test]
+    fn test_color_expression_errors() {
+        let err = palette("colors:\n  a: darken(b, 10%)\n  b: lighten(a, 10%)\n")
+            .err()
+            .unwrap();
+        assert!(err.contains("a -> b -> a"), "{}", err);
+
+        assert!(palette("colors:\n  a: darken(nope, 10%)\n").is_err());
+        assert!(palette("colors:\n  a: darken('#000000')\n").is_err());
+        assert!(palette("colors:\n  a: frobnicate('#000000', 1)\n").is_err());
+        assert!(palette("colors:\n  a: '#00000'\n").is_err());
+        let err = palette("colors:\n  a: darken(white, 15)\n").err().unwrap();
+        assert!(err.contains("must be between 0 and 1 or 0% and 100%"), "{}", err);
+        assert!(palette("colors:\n  a: mix(white, black, 3)\n").is_err());
+        assert!(palette("colors:\n  a: lighten(black, 150%)\n").is_err());
+        assert!(palette("colors:\n  a: alpha(black, 1)\n").is_ok());
+    }
+}
@@ -0,0 +1,305 @@
+use std::collections::HashMap;
+use std::fmt::Write;
+
+use ansi::{bar_colors, window_colors, workspace_colors, Colors};
+use colors;
+use theme::Theme;
+
+pub const FORMATS: &[&str] = &["xresources", "alacritty", "kitty", "foot", "rofi", "dunst"];
+
+// The parts of the theme other programs have a use for.
+struct Roles {
+    background: String,
+    foreground: String,
+    accent: String,
+    accent_text: String,
+    inactive: String,
+    inactive_text: String,
+    urgent: String,
+    urgent_text: String,
+    border: String,
+    separator: String,
+}
+
+impl Roles {
+    fn new(theme: &Theme) -> Roles {
+        let windows = window_colors(theme);
+        let state = |name: &str| &windows.iter().find(|w| w.0 == name).unwrap().1;
+        let (focused, focused_inactive) = (state("focused"), state("focused_inactive"));
+        let (unfocused, urgent) = (state("unfocused"), state("urgent"));
+        let [background, _, separator] = bar_colors(theme);
+
+        Roles {
+            background,
+            foreground: unfocused.text.clone(),
+            accent: focused.background.clone(),
+            accent_text: focused.text.clone(),
+            inactive: focused_inactive.background.clone(),
+            inactive_text: focused_inactive.text.clone(),
+            urgent: urgent.background.clone(),
+            urgent_text: urgent.text.clone(),
+            border: unfocused.border.clone(),
+            separator,
+        }
+    }
+}
+
+// Terminals don't know about transparent colors.
+fn opaque(color: &str) -> String {
+    match colors::parse_hex(color) {
+        Ok(c) => format!("#{:02x}{:02x}{:02x}", c.red, c.green, c.blue),
+        Err(_) => color.to_string(),
+    }
+}
+
+fn header(out: &mut String, comment: &str, theme: &Theme) {
+    if let Some(ref description) = theme.meta.description {
+        writeln!(out, "{} {}", comment, description).unwrap();
+    }
+    writeln!(out, "{} Written by i3-style", comment).unwrap();
+}
+
+// Looks up the name `Theme::named_colors` gave to a color.
+struct Names {
+    names: HashMap<String, String>,
+}
+
+impl Names {
+    fn new(colors: &[(String, String)]) -> Names {
+        Names {
+            names: colors.iter().map(|(n, c)| (c.to_uppercase(), n.clone())).collect(),
+        }
+    }
+
+    fn get(&self, color: &str, prefix: &str) -> String {
+        match self.names.get(&color.to_uppercase()) {
+            Some(name) => format!("{}{}", prefix, name),
+            None => color.to_string(),
+        }
+    }
+}
+
+// The colors are defined once by name, and the terminal colors and the
+// `i3wm` resources for `set_from_resource` refer to them.
+fn xresources(theme: &Theme) -> String {
+    let mut out = String::new();
+    header(&mut out, "!", theme);
+
+    // cpp only takes identifiers as macro names
+    let colors: Vec<(String, String)> = theme
+        .named_colors()
+        .into_iter()
+        .map(|(name, color)| (name.replace('-', "_"), color))
+        .collect();
+    let names = Names::new(&colors);
+    let roles = Roles::new(theme);
+    for (name, color) in &colors {
+        writeln!(out, "#define {} {}", name, color).unwrap();
+    }
+    out.push('\n');
+
+    writeln!(out, "*.background: {}", names.get(&roles.background, "")).unwrap();
+    writeln!(out, "*.foreground: {}", names.get(&roles.foreground, "")).unwrap();
+    writeln!(out, "*.cursorColor: {}", names.get(&roles.accent, "")).unwrap();
+    out.push('\n');
+
+    let mut resource = |name: &str, color: &str| {
+        writeln!(out, "i3wm.{}: {}", name, names.get(color, "")).unwrap();
+    };
+    let groups = window_colors(theme).into_iter().chain(workspace_colors(theme));
+    for (state, Colors { border, background, text, indicator }) in groups {
+        resource(&format!("{}_border", state), &border);
+        resource(&format!("{}_background", state), &background);
+        resource(&format!("{}_text", state), &text);
+        if !state.ends_with("_workspace") {
+            resource(&format!("{}_indicator", state), &indicator);
+        }
+    }
+    let [background, statusline, separator] = bar_colors(theme);
+    resource("bar_background", &background);
+    resource("bar_statusline", &statusline);
+    resource("bar_separator", &separator);
+
+    out
+}
+
+fn alacritty(theme: &Theme) -> String {
+    let r = Roles::new(theme);
+    let mut out = String::new();
+    header(&mut out, "#", theme);
+    writeln!(out, "\n[colors.primary]").unwrap();
+    writeln!(out, "background = \"{}\"", opaque(&r.background)).unwrap();
+    writeln!(out, "foreground = \"{}\"", opaque(&r.foreground)).unwrap();
+    writeln!(out, "\n[colors.cursor]").unwrap();
+    writeln!(out, "text = \"{}\"", opaque(&r.background)).unwrap();
+    writeln!(out, "cursor = \"{}\"", opaque(&r.accent)).unwrap();
+    writeln!(out, "\n[colors.selection]").unwrap();
+    writeln!(out, "text = \"{}\"", opaque(&r.accent_text)).unwrap();
+    writeln!(out, "background = \"{}\"", opaque(&r.accent)).unwrap();
+    out
+}
+
+fn kitty(theme: &Theme) -> String {
+    let r = Roles::new(theme);
+    let mut out = String::new();
+    header(&mut out, "#", theme);
+    let settings = [
+        ("background", &r.background),
+        ("foreground", &r.foreground),
+        ("cursor", &r.accent),
+        ("cursor_text_color", &r.background),
+        ("selection_background", &r.accent),
+        ("selection_foreground", &r.accent_text),
+        ("url_color", &r.accent),
+        ("active_border_color", &r.accent),
+        ("inactive_border_color", &r.border),
+        ("bell_border_color", &r.urgent),
+        ("tab_bar_background", &r.background),
+        ("active_tab_background", &r.accent),
+        ("active_tab_foreground", &r.accent_text),
+        ("inactive_tab_background", &r.inactive),
+        ("inactive_tab_foreground", &r.inactive_text),
+    ];
+    for (name, color) in settings.iter() {
+        writeln!(out, "{} {}", name, opaque(color)).unwrap();
+    }
+    out
+}
+
+fn foot(theme: &Theme) -> String {
+    let r = Roles::new(theme);
+    let hex = |color: &str| opaque(color)[1..].to_string();
+    let mut out = String::new();
+    header(&mut out, "#", theme);
+    writeln!(out, "\n[cursor]").unwrap();
+    writeln!(out, "color={} {}", hex(&r.background), hex(&r.accent)).unwrap();
+    writeln!(out, "\n[colors]").unwrap();
+    writeln!(out, "background={}", hex(&r.background)).unwrap();
+    writeln!(out, "foreground={}", hex(&r.foreground)).unwrap();
+    writeln!(out, "selection-background={}", hex(&r.accent)).unwrap();
+    writeln!(out, "selection-foreground={}", hex(&r.accent_text)).unwrap();
+    writeln!(out, "urls={}", hex(&r.accent)).unwrap();
+    out
+}
+
+// Sets the variables the default rofi theme is built from.
+fn rofi(theme: &Theme) -> String {
+    let colors = theme.named_colors();
+    let names = Names::new(&colors);
+    let r = Roles::new(theme);
+    let mut out = String::new();
+    header(&mut out, "//", theme);
+    out.push_str("\n* {\n");
+    for (name, color) in &colors {
+        writeln!(out, "    {}: {};", name, color.to_lowercase()).unwrap();
+    }
+    out.push('\n');
+
+    let variables = [
+        ("background", &r.background),
+        ("foreground", &r.foreground),
+        ("border-color", &r.accent),
+        ("separatorcolor", &r.separator),
+        ("normal-background", &r.background),
+        ("normal-foreground", &r.foreground),
+        ("alternate-normal-background", &r.background),
+        ("alternate-normal-foreground", &r.foreground),
+        ("selected-normal-background", &r.accent),
+        ("selected-normal-foreground", &r.accent_text),
+        ("active-background", &r.background),
+        ("active-foreground", &r.accent),
+        ("alternate-active-background", &r.background),
+        ("alternate-active-foreground", &r.accent),
+        ("selected-active-background", &r.accent),
+        ("selected-active-foreground", &r.accent_text),
+        ("urgent-background", &r.background),
+        ("urgent-foreground", &r.urgent),
+        ("alternate-urgent-background", &r.background),
+        ("alternate-urgent-foreground", &r.urgent),
+        ("selected-urgent-background", &r.urgent),
+        ("selected-urgent-foreground", &r.urgent_text),
+    ];
+    for (name, color) in variables.iter() {
+        writeln!(out, "    {}: {};", name, names.get(color, "@")).unwrap();
+    }
+    out.push_str("}\n");
+    out
+}
+
+fn dunst(theme: &Theme) -> String {
+    let r = Roles::new(theme);
+    let mut out = String::new();
+    header(&mut out, "#", theme);
+    let urgencies = [
+        ("low", &r.background, &r.inactive_text, &r.border),
+        ("normal", &r.background, &r.foreground, &r.accent),
+        ("critical", &r.urgent, &r.urgent_text, &r.urgent),
+    ];
+    for (urgency, background, foreground, frame) in urgencies.iter() {
+        writeln!(out, "\n[urgency_{}]", urgency).unwrap();
+        writeln!(out, "    background = \"{}\"", opaque(background)).unwrap();
+        writeln!(out, "    foreground = \"{}\"", opaque(foreground)).unwrap();
+        writeln!(out, "    frame_color = \"{}\"", opaque(frame)).unwrap();
+    }
+    out
+}
+
+pub fn export(theme: &Theme, format: &str) -> Result<String, String> {
+    match format {
+        "xresources" => Ok(xresources(theme)),
+        "alacritty" => Ok(alacritty(theme)),
+        "kitty" => Ok(kitty(theme)),
+        "foot" => Ok(foot(theme)),
+        "rofi" => Ok(rofi(theme)),
+        "dunst" => Ok(dunst(theme)),
+        _ => Err(format!("unknown format: {}", format)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::path::Path;
+    use theme::from_yaml;
+    use xresources;
+    use yaml_rust::YamlLoader;
+
+    #[test]
+    fn test_export() {
+        let docs = YamlLoader::load_from_str(
+            "meta:
+  description: Test
+window_colors:
+  focused:
+    border: '#268bd2'
+    background: '#268bd2'
+    text: '#fdf6e3'
+    indicator: '#268bd280'
+bar_colors:
+  background: '#002b36'
+  statusline: '#839496'
+",
+        )
+        .unwrap();
+        let theme = from_yaml(&docs[0]).unwrap();
+
+        // what we write can be read back
+        let exported = export(&theme, "xresources").unwrap();
+        let resources = xresources::resources(&exported, Path::new(".")).unwrap();
+        let palette = xresources::palette(&resources);
+        let color = |name: &str| palette.iter().find(|c| c.0 == name).map(|c| c.1.to_lowercase());
+        assert_eq!(color("background"), Some("#002b36".to_string()));
+        assert_eq!(color("cursor"), Some("#268bd2".to_string()));
+        assert_eq!(color("focused_indicator"), Some("#268bd280".to_string()));
+        assert_eq!(color("urgent_background"), Some("#900000".to_string()));
+
+        let alacritty = export(&theme, "alacritty").unwrap();
+        assert!(alacritty.starts_with("# Test\n# Written by i3-style\n"));
+        assert!(alacritty.contains("[colors.primary]\nbackground = \"#002b36\"\nforeground = \"#888888\"\n"));
+
+        assert!(export(&theme, "foot").unwrap().contains("\n[cursor]\ncolor=002b36 268bd2\n"));
+        assert!(export(&theme, "rofi").unwrap().contains("    selected-normal-background: @"));
+        assert!(export(&theme, "dunst").unwrap().contains("[urgency_critical]\n    background = \"#900000\""));
+    }
+}
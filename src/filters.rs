@@ -1,10 +1,16 @@
 extern crate palette;
+extern crate serde;
 
+use palette::Clamp;
+use palette::Hsl;
 use palette::Hsv;
+use palette::Oklch;
 use palette::Srgb;
 use palette::FromColor;
 use palette::ShiftHueAssign;
-use std::str::FromStr;
+use palette::WithAlpha;
+use colors;
+use self::serde::Deserialize;
 
 pub trait Filter {
     fn apply(&self, color: &Srgb) -> Srgb;
@@ -16,7 +22,9 @@ pub trait Filterable {
 
 impl<F: Filterable> Filterable for Option<F> {
     fn apply_filter(&mut self, filter: &impl Filter) {
-       self.as_mut().map(|x| x.apply_filter(filter));
+        if let Some(x) = self.as_mut() {
+            x.apply_filter(filter);
+        }
     }
 }
 
@@ -29,20 +37,57 @@ impl Filterable for Srgb {
 // TODO remove this abomination
 impl Filterable for String {
     fn apply_filter(&mut self, filter: &impl Filter) {
-        let mut color = Srgb::from_str(self).unwrap().into_format();
-        color.apply_filter(filter);
-        *self = format!("#{:x}", color.into_format::<u8>());
+        // colors that aren't hex are left as they are
+        let color = match colors::parse_hex(self) {
+            Ok(c) => c,
+            Err(_) => return,
+        };
+        let mut rgb: Srgb = color.color.into_format();
+        rgb.apply_filter(filter);
+        *self = colors::to_hex(rgb.into_format::<u8>().with_alpha(color.alpha));
     }
 }
 
+// The color space hues are rotated in. Oklch keeps the lightness of the
+// colors as they are seen, where HSV and HSL don't.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ColorSpace {
+    #[default]
+    Hsv,
+    Hsl,
+    Oklch,
+}
+
 pub struct HueFilter {
-    pub hue: f32
+    pub hue: f32,
+    pub space: ColorSpace,
+}
+
+impl HueFilter {
+    pub fn new(hue: f32) -> HueFilter {
+        HueFilter { hue, space: ColorSpace::Hsv }
+    }
 }
 
 impl Filter for HueFilter {
     fn apply(&self, color: &Srgb) -> Srgb {
-        let mut color: Hsv = Hsv::from_color(color.into_format());
-        color.shift_hue_assign(self.hue);
-        Srgb::from_color(color)
+        match self.space {
+            ColorSpace::Hsv => {
+                let mut color: Hsv = Hsv::from_color(color.into_format());
+                color.shift_hue_assign(self.hue);
+                Srgb::from_color(color)
+            }
+            ColorSpace::Hsl => {
+                let mut color: Hsl = Hsl::from_color(color.into_format());
+                color.shift_hue_assign(self.hue);
+                Srgb::from_color(color)
+            }
+            ColorSpace::Oklch => {
+                let mut color: Oklch = Oklch::from_color(color.into_format());
+                color.shift_hue_assign(self.hue);
+                Srgb::from_color(color).clamp()
+            }
+        }
     }
 }
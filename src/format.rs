@@ -0,0 +1,205 @@
+extern crate serde;
+extern crate serde_json;
+extern crate serde_path_to_error;
+extern crate toml;
+extern crate yaml_rust;
+
+use self::serde::de::DeserializeOwned;
+use self::serde::Serialize;
+use self::serde_json::{Map, Number, Value};
+use linked_hash_map::LinkedHashMap;
+use std::path::Path;
+use theme::{Theme, ThemeFile};
+use yaml_rust::{Yaml, YamlEmitter, YamlLoader};
+
+pub const FORMATS: &[&str] = &["yaml", "toml", "json"];
+
+// The extensions theme files can have, in the order they are looked for.
+pub const EXTENSIONS: &[&str] = &["yaml", "yml", "toml", "json"];
+
+pub fn from_extension(extension: &str) -> Option<&'static str> {
+    match extension {
+        "yaml" | "yml" => Some("yaml"),
+        "toml" => Some("toml"),
+        "json" => Some("json"),
+        _ => None,
+    }
+}
+
+pub fn from_path(path: &Path) -> Option<&'static str> {
+    from_extension(path.extension()?.to_str()?)
+}
+
+// Files without an extension are YAML unless they are obviously something
+// else, which is the case when YAML doesn't make a map of them.
+pub fn guess(contents: &str) -> &'static str {
+    if contents.trim_start().starts_with('{') {
+        return "json";
+    }
+    match YamlLoader::load_from_str(contents) {
+        Ok(ref docs) if docs.first().is_some_and(|d| d.as_hash().is_some()) => "yaml",
+        _ if contents.parse::<toml::Value>().is_ok() => "toml",
+        _ => "yaml",
+    }
+}
+
+// The format of a theme file, by its extension or else by its contents.
+pub fn format_of(path: &Path, contents: &str) -> &'static str {
+    from_path(path).unwrap_or_else(|| guess(contents))
+}
+
+// Reads a theme document. Documents are YAML trees whatever the format, and
+// are read into a `ThemeFile` with `from_doc`.
+pub fn parse(contents: &str, format: &str) -> Result<Yaml, String> {
+    match format {
+        "yaml" => {
+            let mut docs = YamlLoader::load_from_str(contents).map_err(|e| e.to_string())?;
+            if docs.is_empty() {
+                return Err(String::from("the file is empty"));
+            }
+            Ok(docs.remove(0))
+        }
+        "toml" => toml::from_str(contents).map(|v| json_to_yaml(&v)).map_err(|e| e.to_string()),
+        "json" => serde_json::from_str(contents).map(|v| json_to_yaml(&v)).map_err(|e| e.to_string()),
+        _ => Err(format!("unknown format: {}", format)),
+    }
+}
+
+fn json_to_yaml(value: &Value) -> Yaml {
+    match value {
+        Value::Null => Yaml::Null,
+        Value::Bool(b) => Yaml::Boolean(*b),
+        Value::Number(n) => match n.as_i64() {
+            Some(i) => Yaml::Integer(i),
+            None => Yaml::Real(n.to_string()),
+        },
+        Value::String(s) => Yaml::String(s.clone()),
+        Value::Array(items) => Yaml::Array(items.iter().map(json_to_yaml).collect()),
+        Value::Object(map) => {
+            let mut hash = LinkedHashMap::new();
+            for (key, value) in map {
+                hash.insert(Yaml::String(key.clone()), json_to_yaml(value));
+            }
+            Yaml::Hash(hash)
+        }
+    }
+}
+
+fn yaml_to_json(doc: &Yaml) -> Value {
+    match doc {
+        Yaml::String(s) => Value::String(s.clone()),
+        Yaml::Integer(i) => Value::from(*i),
+        Yaml::Real(r) => match r.parse::<f64>().ok().and_then(Number::from_f64) {
+            Some(n) => Value::Number(n),
+            None => Value::String(r.clone()),
+        },
+        Yaml::Boolean(b) => Value::Bool(*b),
+        Yaml::Array(items) => Value::Array(items.iter().map(yaml_to_json).collect()),
+        Yaml::Hash(hash) => {
+            let mut map = Map::new();
+            for (key, value) in hash {
+                let key = match key {
+                    Yaml::String(s) | Yaml::Real(s) => s.clone(),
+                    Yaml::Integer(i) => i.to_string(),
+                    Yaml::Boolean(b) => b.to_string(),
+                    _ => format!("{:?}", key),
+                };
+                map.insert(key, yaml_to_json(value));
+            }
+            Value::Object(map)
+        }
+        Yaml::Null | Yaml::BadValue | Yaml::Alias(_) => Value::Null,
+    }
+}
+
+// Reads a document with serde. Errors start with the key they are about, so
+// they are the same for every format.
+pub fn from_doc<T: DeserializeOwned>(doc: &Yaml) -> Result<T, String> {
+    serde_path_to_error::deserialize(yaml_to_json(doc)).map_err(|e| {
+        let path = e.path().to_string();
+        if path == "." {
+            e.inner().to_string()
+        } else {
+            format!("{}: {}", path, e.inner())
+        }
+    })
+}
+
+pub fn to_doc<T: Serialize>(value: &T) -> Result<Yaml, String> {
+    serde_json::to_value(value).map(|v| json_to_yaml(&v)).map_err(|e| e.to_string())
+}
+
+pub fn emit_yaml(doc: &Yaml) -> Result<String, String> {
+    let mut out = String::new();
+    YamlEmitter::new(&mut out)
+        .dump(doc)
+        .map_err(|e| format!("{:?}", e))?;
+    Ok(out)
+}
+
+pub fn emit(theme: &Theme, format: &str) -> Result<String, String> {
+    match format {
+        "yaml" => emit_yaml(&to_doc(&ThemeFile::from(theme.clone()))?),
+        "toml" => toml::to_string(theme)
+            .map(|s| s.trim_end().to_string())
+            .map_err(|e| e.to_string()),
+        "json" => serde_json::to_string_pretty(theme).map_err(|e| e.to_string()),
+        _ => Err(format!("unknown format: {}", format)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use theme::from_yaml;
+
+    #[test]
+    fn test_formats() {
+        let solarized = from_yaml(&parse(include_str!("../themes/solarized"), "yaml").unwrap()).unwrap();
+        let expected = emit(&solarized, "yaml").unwrap();
+
+        for format in &["toml", "json"] {
+            let contents = emit(&solarized, format).unwrap();
+            assert_eq!(guess(&contents), *format);
+
+            let theme = from_yaml(&parse(&contents, format).unwrap()).unwrap();
+            assert_eq!(emit(&theme, "yaml").unwrap(), expected);
+        }
+
+        let toml = emit(&solarized, "toml").unwrap();
+        assert!(toml.starts_with("[meta]\ndescription = "), "{}", toml);
+        assert!(toml.contains("\n[window_colors.focused]\nborder = \""), "{}", toml);
+
+        // the model checks keys and types the same way in every format
+        let invalid = [
+            ("yaml", "window_colors:\n  focussed:\n    border: '#000000'\n"),
+            ("toml", "[window_colors.focussed]\nborder = \"#000000\"\n"),
+            ("json", "{\"window_colors\": {\"focussed\": {\"border\": \"#000000\"}}}"),
+        ];
+        for (format, contents) in invalid.iter() {
+            let err = from_yaml(&parse(contents, format).unwrap()).unwrap_err();
+            assert!(err.starts_with("window_colors.focussed: unknown field `focussed`"), "{}", err);
+        }
+        let invalid = [
+            ("yaml", "gaps:\n  inner: '8'\n"),
+            ("toml", "[gaps]\ninner = \"8\"\n"),
+            ("json", "{\"gaps\": {\"inner\": \"8\"}}"),
+        ];
+        for (format, contents) in invalid.iter() {
+            let err = from_yaml(&parse(contents, format).unwrap()).unwrap_err();
+            assert_eq!(err, "gaps.inner: invalid type: string \"8\", expected i64");
+        }
+        let err = from_yaml(&parse("window_colors:\n  focused:\n    border: 123\n", "yaml").unwrap()).unwrap_err();
+        assert!(err.starts_with("window_colors.focused.border: invalid type: integer `123`"), "{}", err);
+        let doc = parse("{\"meta\": {\"version\": 1}}", "json").unwrap();
+        assert_eq!(from_yaml(&doc).unwrap().meta.version.unwrap(), "1");
+        let doc = parse("{\"font\": \"pango:Terminus 9\", \"borders\": {\"default_border\": 2}}", "json").unwrap();
+        let theme = from_yaml(&doc).unwrap();
+        assert_eq!(theme.font.unwrap().window.unwrap(), "pango:Terminus 9");
+        assert_eq!(theme.borders.unwrap().default_border.unwrap(), "pixel 2");
+        assert_eq!(guess(include_str!("../themes/solarized")), "yaml");
+        assert_eq!(from_path(Path::new("ocean.yml")), Some("yaml"));
+        assert_eq!(from_path(Path::new("ocean")), None);
+    }
+}
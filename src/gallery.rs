@@ -0,0 +1,232 @@
+use std::fmt::Write;
+use std::fs;
+use std::path::Path;
+
+use render;
+use theme::{ColorGroup, Theme};
+
+const STYLE: &str = "
+body { background: #202020; color: #ddd; font-family: sans-serif; margin: 2em; }
+h1 { font-weight: normal; }
+main { display: grid; grid-template-columns: repeat(auto-fill, minmax(420px, 1fr)); gap: 2em; }
+article { background: #2a2a2a; border-radius: 6px; padding: 1em; }
+article img { width: 100%; display: block; }
+h2 { margin: 0.5em 0 0.2em; font-size: 1.2em; }
+.meta { color: #999; font-size: 0.9em; margin: 0.2em 0; }
+.palette { display: flex; flex-wrap: wrap; gap: 2px; margin: 0.6em 0; }
+.palette span { width: 22px; height: 22px; border-radius: 3px; }
+pre { display: flex; align-items: center; background: #181818; padding: 0.5em; margin: 0; }
+pre code { flex: 1; overflow-x: auto; }
+button { background: #444; color: #ddd; border: none; border-radius: 3px; cursor: pointer; }
+a { color: #8ab4f8; }
+";
+
+const SCRIPT: &str = "
+for (const button of document.querySelectorAll('button[data-copy]')) {
+  button.addEventListener('click', () => {
+    navigator.clipboard.writeText(button.dataset.copy);
+    button.textContent = 'copied';
+  });
+}
+";
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+// Percent-encodes everything but the characters that are safe anywhere in
+// a URL path, so a file name can't end the attribute or the path.
+fn encode(text: &str) -> String {
+    let mut out = String::new();
+    for byte in text.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => out.push(byte as char),
+            _ => write!(out, "%{:02X}", byte).unwrap(),
+        }
+    }
+    out
+}
+
+fn is_web_link(url: &str) -> bool {
+    let url = url.to_lowercase();
+    url.starts_with("http://") || url.starts_with("https://")
+}
+
+fn push_group(colors: &mut Vec<String>, group: &Option<ColorGroup>) {
+    if let Some(ref g) = *group {
+        for color in [&g.border, &g.background, &g.text, &g.indicator].iter() {
+            if let Some(ref c) = **color {
+                colors.push(c.clone());
+            }
+        }
+    }
+}
+
+// Every color the theme uses, each once, in the order they appear in it.
+pub fn palette(theme: &Theme) -> Vec<String> {
+    let mut colors = Vec::new();
+
+    if let Some(ref wc) = theme.window_colors {
+        push_group(&mut colors, &wc.focused);
+        push_group(&mut colors, &wc.focused_inactive);
+        push_group(&mut colors, &wc.unfocused);
+        push_group(&mut colors, &wc.urgent);
+    }
+    if let Some(ref bc) = theme.bar_colors {
+        for color in [&bc.background, &bc.statusline, &bc.separator].iter() {
+            if let Some(ref c) = **color {
+                colors.push(c.clone());
+            }
+        }
+        push_group(&mut colors, &bc.focused_workspace);
+        push_group(&mut colors, &bc.active_workspace);
+        push_group(&mut colors, &bc.inactive_workspace);
+        push_group(&mut colors, &bc.urgent_workspace);
+    }
+    if let Some(ref sc) = theme.status_colors {
+        for color in [&sc.good, &sc.degraded, &sc.bad, &sc.separator].iter() {
+            if let Some(ref c) = **color {
+                colors.push(c.clone());
+            }
+        }
+    }
+
+    let mut unique: Vec<String> = Vec::new();
+    for color in colors {
+        let color = color.to_lowercase();
+        if !unique.contains(&color) {
+            unique.push(color);
+        }
+    }
+    unique
+}
+
+fn card(out: &mut String, name: &str, theme: &Theme) {
+    let meta = &theme.meta;
+    let file = encode(name);
+    let name = escape(name);
+
+    writeln!(out, "<article id=\"{}\">", file).unwrap();
+    writeln!(out, "<img src=\"previews/{}.svg\" alt=\"{}\">", file, name).unwrap();
+    writeln!(out, "<h2>{}</h2>", name).unwrap();
+    if let Some(ref description) = meta.description {
+        writeln!(out, "<p>{}</p>", escape(description)).unwrap();
+    }
+
+    let mut details = Vec::new();
+    if let Some(ref author) = meta.author {
+        details.push(format!("by {}", escape(author)));
+    }
+    if let Some(ref license) = meta.license {
+        details.push(escape(license));
+    }
+    if let Some(variant) = meta.variant {
+        details.push(variant.as_str().to_string());
+    }
+    if !meta.tags.is_empty() {
+        details.push(escape(&meta.tags.join(", ")));
+    }
+    if let Some(homepage) = meta.homepage.as_ref().filter(|h| is_web_link(h)) {
+        details.push(format!("<a href=\"{}\">homepage</a>", escape(homepage)));
+    }
+    if !details.is_empty() {
+        writeln!(out, "<p class=\"meta\">{}</p>", details.join(" · ")).unwrap();
+    }
+
+    out.push_str("<div class=\"palette\">");
+    for color in palette(theme) {
+        write!(out, "<span style=\"background: {}\" title=\"{}\"></span>", escape(&color), escape(&color)).unwrap();
+    }
+    out.push_str("</div>\n");
+
+    let command = format!("i3-style apply {} --in-place --reload", name);
+    writeln!(
+        out,
+        "<pre><code>{}</code> <button data-copy=\"{}\">copy</button></pre>",
+        command, command
+    )
+    .unwrap();
+    out.push_str("</article>\n");
+}
+
+pub fn page(themes: &[(String, Theme)]) -> String {
+    let mut out = String::new();
+    out.push_str("<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n");
+    out.push_str("<title>i3-style themes</title>\n");
+    writeln!(out, "<style>{}</style>", STYLE).unwrap();
+    out.push_str("</head>\n<body>\n<h1>i3-style themes</h1>\n<main>\n");
+    for (name, theme) in themes {
+        card(&mut out, name, theme);
+    }
+    out.push_str("</main>\n");
+    writeln!(out, "<script>{}</script>", SCRIPT).unwrap();
+    out.push_str("</body>\n</html>\n");
+    out
+}
+
+// Writes index.html and a preview for every theme to `dir`.
+pub fn write(dir: &Path, themes: &[(String, Theme)]) -> Result<(), String> {
+    let previews = dir.join("previews");
+    fs::create_dir_all(&previews).map_err(|e| format!("{}: {}", previews.display(), e))?;
+
+    for (name, theme) in themes {
+        let path = previews.join(format!("{}.svg", name));
+        fs::write(&path, render::svg(theme)).map_err(|e| format!("{}: {}", path.display(), e))?;
+    }
+
+    let index = dir.join("index.html");
+    fs::write(&index, page(themes)).map_err(|e| format!("{}: {}", index.display(), e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    extern crate tempfile;
+
+    use self::tempfile::tempdir;
+    use theme::from_yaml;
+    use yaml_rust::YamlLoader;
+
+    #[test]
+    fn test_gallery() {
+        let docs = YamlLoader::load_from_str(
+            "meta:
+  description: Black & <white>
+  author: someone
+  tags: [mono]
+window_colors:
+  focused:
+    border: '#000000'
+    background: '#FFFFFF'
+    text: '#000000'
+",
+        )
+        .unwrap();
+        let themes = vec![("mono".to_string(), from_yaml(&docs[0]).unwrap())];
+
+        assert_eq!(palette(&themes[0].1), vec!["#000000", "#ffffff"]);
+
+        let dir = tempdir().unwrap();
+        write(dir.path(), &themes).unwrap();
+        assert!(dir.path().join("previews/mono.svg").is_file());
+
+        let index = fs::read_to_string(dir.path().join("index.html")).unwrap();
+        assert!(index.contains("<p>Black &amp; &lt;white&gt;</p>"));
+        assert!(index.contains("<p class=\"meta\">by someone · mono</p>"));
+        assert!(index.contains("data-copy=\"i3-style apply mono --in-place --reload\""));
+
+        let mut theme = themes[0].1.clone();
+        theme.meta.homepage = Some("javascript:alert(1)".to_string());
+        let index = page(&[("my \"theme\"".to_string(), theme.clone())]);
+        assert!(!index.contains("href"), "{}", index);
+        assert!(index.contains("<article id=\"my%20%22theme%22\">"), "{}", index);
+        assert!(index.contains("<img src=\"previews/my%20%22theme%22.svg\" alt=\"my &quot;theme&quot;\">"));
+        theme.meta.homepage = Some("https://example.com/?a=1&b=2".to_string());
+        let index = page(&[("mono".to_string(), theme)]);
+        assert!(index.contains("<a href=\"https://example.com/?a=1&amp;b=2\">homepage</a>"));
+    }
+}
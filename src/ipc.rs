@@ -0,0 +1,384 @@
+extern crate serde_json;
+
+use std::env;
+use std::io;
+use std::io::prelude::*;
+use std::os::unix::net::UnixStream;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use self::serde_json::Value;
+use theme::{ColorGroup, Theme};
+
+// The i3 IPC protocol, which sway speaks as well. Every message is the magic
+// string, the length of the payload and the message type in native byte order,
+// followed by the payload.
+const MAGIC: &[u8] = b"i3-ipc";
+
+pub const RUN_COMMAND: u32 = 0;
+pub const GET_BAR_CONFIG: u32 = 6;
+
+fn socket_path_from_wm(wm: &str) -> Option<PathBuf> {
+    let output = Command::new(wm)
+        .arg("--get-socketpath")
+        .stderr(Stdio::null())
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let path = String::from_utf8(output.stdout).ok()?;
+    let path = path.trim();
+    if path.is_empty() {
+        return None;
+    }
+    Some(PathBuf::from(path))
+}
+
+pub fn socket_path() -> Result<PathBuf, String> {
+    for var in &["I3SOCK", "SWAYSOCK"] {
+        if let Some(path) = env::var_os(var) {
+            if !path.is_empty() {
+                return Ok(PathBuf::from(path));
+            }
+        }
+    }
+
+    for wm in &["i3", "sway"] {
+        if let Some(path) = socket_path_from_wm(wm) {
+            return Ok(path);
+        }
+    }
+
+    Err("Could not find the IPC socket (is i3 or sway running?)".to_string())
+}
+
+pub struct Connection {
+    stream: UnixStream,
+}
+
+impl Connection {
+    pub fn connect(path: &Path) -> io::Result<Connection> {
+        Ok(Connection {
+            stream: UnixStream::connect(path)?,
+        })
+    }
+
+    pub fn connect_default() -> Result<Connection, String> {
+        let path = socket_path()?;
+        Connection::connect(&path).map_err(|e| format!("{}: {}", path.display(), e))
+    }
+
+    // Sends a message and returns the payload of the reply.
+    pub fn request(&mut self, message_type: u32, payload: &[u8]) -> io::Result<Vec<u8>> {
+        let mut message = Vec::with_capacity(MAGIC.len() + 8 + payload.len());
+        message.extend_from_slice(MAGIC);
+        message.extend_from_slice(&(payload.len() as u32).to_ne_bytes());
+        message.extend_from_slice(&message_type.to_ne_bytes());
+        message.extend_from_slice(payload);
+        self.stream.write_all(&message)?;
+
+        let mut header = [0u8; 14];
+        self.stream.read_exact(&mut header)?;
+        if &header[..6] != MAGIC {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "reply does not start with the i3-ipc magic string",
+            ));
+        }
+
+        let mut field = [0u8; 4];
+        field.copy_from_slice(&header[6..10]);
+        let mut reply = vec![0u8; u32::from_ne_bytes(field) as usize];
+        self.stream.read_exact(&mut reply)?;
+
+        field.copy_from_slice(&header[10..14]);
+        let reply_type = u32::from_ne_bytes(field);
+        if reply_type != message_type {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("expected a reply of type {}, got type {}", message_type, reply_type),
+            ));
+        }
+        Ok(reply)
+    }
+
+    fn request_json(&mut self, message_type: u32, payload: &str) -> Result<Value, String> {
+        let reply = self
+            .request(message_type, payload.as_bytes())
+            .map_err(|e| e.to_string())?;
+        serde_json::from_slice(&reply).map_err(|e| format!("invalid reply: {}", e))
+    }
+
+    // Runs a command like `i3-msg` would. The reply has an entry for every
+    // command in the payload and any of them can fail.
+    pub fn run_command(&mut self, command: &str) -> Result<(), String> {
+        let reply = self.request_json(RUN_COMMAND, command)?;
+
+        let results = match reply {
+            Value::Array(results) => results,
+            // some versions reply with a single object
+            result => vec![result],
+        };
+
+        for result in results {
+            if result["success"].as_bool() != Some(true) {
+                let error = result["error"].as_str().unwrap_or("unknown error");
+                return Err(error.to_string());
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn bar_ids(&mut self) -> Result<Vec<String>, String> {
+        let reply = self.request_json(GET_BAR_CONFIG, "")?;
+        match reply.as_array() {
+            Some(ids) => Ok(ids
+                .iter()
+                .filter_map(|id| id.as_str().map(String::from))
+                .collect()),
+            None => Err("invalid reply: expected a list of bar ids".to_string()),
+        }
+    }
+
+    // Sets the colors of the theme on the running sway without touching the
+    // config. i3 doesn't take `client.*` or bar colors at runtime.
+    pub fn apply_theme(&mut self, theme: &Theme) -> Result<(), String> {
+        let bar_ids = if theme.bar_colors.is_some() {
+            self.bar_ids()?
+        } else {
+            Vec::new()
+        };
+
+        for command in theme_commands(theme, &bar_ids) {
+            self.run_command(&command)
+                .map_err(|e| format!("`{}` failed: {}", command, e))?;
+        }
+
+        Ok(())
+    }
+}
+
+// Like the config, the window manager only takes groups with at least the
+// border, background and text colors.
+fn group_colors(group: &Option<ColorGroup>, with_indicator: bool) -> Option<String> {
+    let group = group.as_ref()?;
+    let mut colors = format!(
+        "{} {} {}",
+        group.border.as_ref()?,
+        group.background.as_ref()?,
+        group.text.as_ref()?
+    );
+    if with_indicator {
+        if let Some(ref indicator) = group.indicator {
+            colors.push(' ');
+            colors.push_str(indicator);
+        }
+    }
+    Some(colors)
+}
+
+// The runtime commands that set the colors of `theme` on the bars with the
+// given ids.
+pub fn theme_commands(theme: &Theme, bar_ids: &[String]) -> Vec<String> {
+    let mut commands = Vec::new();
+
+    if let Some(ref wc) = theme.window_colors {
+        let groups = [
+            ("focused", &wc.focused),
+            ("focused_inactive", &wc.focused_inactive),
+            ("unfocused", &wc.unfocused),
+            ("urgent", &wc.urgent),
+        ];
+        for (name, group) in groups.iter() {
+            if let Some(colors) = group_colors(group, true) {
+                commands.push(format!("client.{} {}", name, colors));
+            }
+        }
+    }
+
+    if let Some(ref bc) = theme.bar_colors {
+        let mut colors = Vec::new();
+        let singles = [
+            ("separator", &bc.separator),
+            ("background", &bc.background),
+            ("statusline", &bc.statusline),
+        ];
+        for (name, color) in singles.iter() {
+            if let Some(ref color) = **color {
+                colors.push(format!("{} {}", name, color));
+            }
+        }
+        let groups = [
+            ("focused_workspace", &bc.focused_workspace),
+            ("active_workspace", &bc.active_workspace),
+            ("inactive_workspace", &bc.inactive_workspace),
+            ("urgent_workspace", &bc.urgent_workspace),
+        ];
+        for (name, group) in groups.iter() {
+            if let Some(group) = group_colors(group, false) {
+                colors.push(format!("{} {}", name, group));
+            }
+        }
+
+        for id in bar_ids {
+            for color in &colors {
+                commands.push(format!("bar {} colors {}", id, color));
+            }
+        }
+    }
+
+    commands
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    extern crate tempfile;
+
+    use self::tempfile::tempdir;
+    use std::os::unix::net::UnixListener;
+    use std::thread;
+    use theme::from_yaml;
+    use yaml_rust::YamlLoader;
+
+    // Answers each message with the next of `replies` and returns what it received.
+    fn serve(listener: UnixListener, replies: Vec<&'static str>) -> thread::JoinHandle<Vec<(u32, String)>> {
+        thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut received = Vec::new();
+            for reply in replies {
+                let mut header = [0u8; 14];
+                stream.read_exact(&mut header).unwrap();
+                assert_eq!(&header[..6], MAGIC);
+                let mut field = [0u8; 4];
+                field.copy_from_slice(&header[6..10]);
+                let mut payload = vec![0u8; u32::from_ne_bytes(field) as usize];
+                field.copy_from_slice(&header[10..14]);
+                let message_type = u32::from_ne_bytes(field);
+                stream.read_exact(&mut payload).unwrap();
+                received.push((message_type, String::from_utf8(payload).unwrap()));
+
+                let mut message = Vec::new();
+                message.extend_from_slice(MAGIC);
+                message.extend_from_slice(&(reply.len() as u32).to_ne_bytes());
+                message.extend_from_slice(&message_type.to_ne_bytes());
+                message.extend_from_slice(reply.as_bytes());
+                stream.write_all(&message).unwrap();
+            }
+            received
+        })
+    }
+
+    #[test]
+    fn test_run_command() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("ipc.sock");
+        let listener = UnixListener::bind(&path).unwrap();
+        let server = serve(
+            listener,
+            vec![
+                r#"[{"success":true}]"#,
+                r#"[{"success":false,"parse_error":true,"error":"Expected one of these tokens: <end>"}]"#,
+            ],
+        );
+
+        let mut connection = Connection::connect(&path).unwrap();
+        assert_eq!(connection.run_command("reload"), Ok(()));
+        assert_eq!(
+            connection.run_command("relaod"),
+            Err("Expected one of these tokens: <end>".to_string())
+        );
+
+        let received = server.join().unwrap();
+        assert_eq!(
+            received,
+            vec![
+                (RUN_COMMAND, "reload".to_string()),
+                (RUN_COMMAND, "relaod".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_reply_type() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("ipc.sock");
+        let listener = UnixListener::bind(&path).unwrap();
+        let server = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut header = [0u8; 14];
+            stream.read_exact(&mut header).unwrap();
+            let mut message = Vec::new();
+            message.extend_from_slice(MAGIC);
+            message.extend_from_slice(&2u32.to_ne_bytes());
+            message.extend_from_slice(&GET_BAR_CONFIG.to_ne_bytes());
+            message.extend_from_slice(b"[]");
+            stream.write_all(&message).unwrap();
+        });
+
+        let mut connection = Connection::connect(&path).unwrap();
+        let err = connection.request(RUN_COMMAND, b"").unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert_eq!(err.to_string(), "expected a reply of type 0, got type 6");
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn test_apply_theme() {
+        let docs = YamlLoader::load_from_str(
+            "window_colors:
+  focused:
+    border: '#111111'
+    background: '#222222'
+    text: '#333333'
+    indicator: '#444444'
+  unfocused:
+    border: '#111111'
+bar_colors:
+  background: '#000000'
+  focused_workspace:
+    border: '#111111'
+    background: '#222222'
+    text: '#333333'
+",
+        )
+        .unwrap();
+        let theme = from_yaml(&docs[0]).unwrap();
+
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("ipc.sock");
+        let listener = UnixListener::bind(&path).unwrap();
+        let server = serve(
+            listener,
+            vec![
+                r#"["bar-0"]"#,
+                r#"[{"success":true}]"#,
+                r#"[{"success":true}]"#,
+                r#"[{"success":true}]"#,
+            ],
+        );
+
+        let mut connection = Connection::connect(&path).unwrap();
+        assert_eq!(connection.apply_theme(&theme), Ok(()));
+
+        let received = server.join().unwrap();
+        assert_eq!(
+            received,
+            vec![
+                (GET_BAR_CONFIG, "".to_string()),
+                (
+                    RUN_COMMAND,
+                    "client.focused #111111 #222222 #333333 #444444".to_string()
+                ),
+                (RUN_COMMAND, "bar bar-0 colors background #000000".to_string()),
+                (
+                    RUN_COMMAND,
+                    "bar bar-0 colors focused_workspace #111111 #222222 #333333".to_string()
+                ),
+            ]
+        );
+    }
+}
@@ -0,0 +1,128 @@
+//! Themes for the window decorations and bar of i3 and sway.
+//!
+//! A `Theme` is loaded by name with `load_theme`, from a file or string in
+//! any of the `format::FORMATS`, or extracted from a config with
+//! `theme::from_config`. `writer::write_theme` applies it to a config.
+
+#[macro_use]
+extern crate lazy_static;
+extern crate includedir;
+extern crate linked_hash_map;
+extern crate palette;
+extern crate phf;
+extern crate yaml_rust;
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use yaml_rust::Yaml;
+
+pub mod ansi;
+pub mod base16;
+pub mod catalog;
+pub mod colors;
+pub mod export;
+pub mod filters;
+pub mod format;
+pub mod gallery;
+pub mod ipc;
+pub mod lint;
+pub mod pick;
+pub mod render;
+pub mod settings;
+pub mod status;
+pub mod theme;
+pub mod validate;
+pub mod wal;
+pub mod wallpaper;
+pub mod writer;
+pub mod xresources;
+
+pub use filters::{ColorSpace, Filter, Filterable, HueFilter};
+pub use theme::Theme;
+
+mod embedded {
+    include!(concat!(env!("OUT_DIR"), "/data.rs"));
+}
+
+// The names of the themes that come with i3-style.
+pub fn embedded_theme_names() -> Vec<String> {
+    let prefix_count = "./themes/".chars().count();
+    embedded::FILES
+        .file_names()
+        .map(|file| file.chars().skip(prefix_count).collect())
+        .collect()
+}
+
+pub fn embedded_theme(name: &str) -> Option<String> {
+    let file = format!("./themes/{}", name);
+
+    if !embedded::FILES.is_available(&file) {
+        return None;
+    }
+
+    let contents = String::from_utf8(embedded::FILES.get(&file).unwrap().to_vec())
+        .expect("Theme yaml is not utf-8");
+
+    Some(contents)
+}
+
+// Themes can be YAML, TOML or JSON, which is told by the extension of `path`
+// or else by the contents.
+pub fn parse_theme_doc(contents: &str, path: &Path) -> Result<Yaml, String> {
+    let format = format::format_of(path, contents);
+    let doc = format::parse(contents, format)
+        .map_err(|e| format!("Could not parse {} for theme: {}", format, e))?;
+    base16::convert_if_scheme(doc)
+}
+
+pub fn theme_from_str(contents: &str, format: &str) -> Result<Theme, String> {
+    let doc = format::parse(contents, format)?;
+    theme::from_yaml(&base16::convert_if_scheme(doc)?)
+}
+
+pub fn theme_from_path(path: &Path) -> Result<Theme, String> {
+    let contents = fs::read_to_string(path).map_err(|e| e.to_string())?;
+    theme::from_yaml(&parse_theme_doc(&contents, path)?)
+}
+
+// Themes are looked up by name in the user theme directories first, then in
+// the embedded themes, and finally treated as a path.
+pub fn read_theme_doc(name: &str) -> Result<Yaml, String> {
+    let read = |path: &Path| fs::read_to_string(path).map_err(|e| e.to_string());
+    let (path, contents) = match catalog::find_theme(name) {
+        Some(path) => {
+            let contents = read(&path)?;
+            (path, contents)
+        }
+        None => match embedded_theme(name) {
+            Some(contents) => (PathBuf::from(name), contents),
+            None => (PathBuf::from(name), read(Path::new(name))?),
+        },
+    };
+    parse_theme_doc(&contents, &path)
+}
+
+pub fn load_theme(name: &str) -> Result<Theme, String> {
+    read_theme_doc(name).and_then(|doc| theme::from_yaml(&doc))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_load_and_write() {
+        let mut theme = load_theme("solarized").unwrap();
+        theme.apply_filter(&HueFilter::new(180.0));
+
+        let config = "bar {\n  status_command i3status\n}\n";
+        let mut out = Vec::new();
+        writer::write_theme(config.as_bytes(), &mut out, &theme).unwrap();
+        let out = String::from_utf8(out).unwrap();
+        assert!(out.starts_with("bar {\n  status_command i3status\n  colors {\n"));
+        assert!(out.contains("\nclient.focused "));
+
+        assert!(theme_from_str("[meta]\ndescription = \"x\"\n", "toml").is_ok());
+        assert!(load_theme("no-such-theme").is_err());
+    }
+}
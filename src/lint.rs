@@ -0,0 +1,156 @@
+extern crate yaml_rust;
+
+use colors;
+use theme::{ColorGroup, Theme};
+use yaml_rust::Yaml;
+
+// Text with less contrast than this against its background is hard to read.
+const MIN_CONTRAST: f32 = 3.0;
+
+fn keys(yaml: &Yaml) -> Vec<String> {
+    match yaml.as_hash() {
+        Some(hash) => hash
+            .keys()
+            .map(|k| k.as_str().map_or(format!("{:?}", k), String::from))
+            .collect(),
+        None => Vec::new(),
+    }
+}
+
+// Every word in a color spec that could be the name of a palette color.
+fn words(spec: &str) -> Vec<&str> {
+    spec.split(|c: char| !(c.is_alphanumeric() || c == '_' || c == '-'))
+        .filter(|w| !w.is_empty())
+        .collect()
+}
+
+fn collect_specs<'a>(yaml: &'a Yaml, specs: &mut Vec<&'a str>) {
+    match *yaml {
+        Yaml::String(ref s) => specs.push(s),
+        Yaml::Hash(ref hash) => {
+            for value in hash.values() {
+                collect_specs(value, specs);
+            }
+        }
+        _ => (),
+    }
+}
+
+fn luminance(hex: &str) -> Option<f32> {
+    let color = colors::parse_hex(hex).ok()?;
+    let channel = |c: u8| {
+        let c = c as f32 / 255.0;
+        if c <= 0.03928 {
+            c / 12.92
+        } else {
+            ((c + 0.055) / 1.055).powf(2.4)
+        }
+    };
+    Some(0.2126 * channel(color.red) + 0.7152 * channel(color.green) + 0.0722 * channel(color.blue))
+}
+
+pub fn contrast(a: &str, b: &str) -> Option<f32> {
+    let (a, b) = (luminance(a)?, luminance(b)?);
+    let (light, dark) = if a > b { (a, b) } else { (b, a) };
+    Some((light + 0.05) / (dark + 0.05))
+}
+
+fn check_contrast(warnings: &mut Vec<String>, name: &str, group: &Option<ColorGroup>) {
+    let group = match *group {
+        Some(ref g) => g,
+        None => return,
+    };
+    if let (Some(text), Some(background)) = (&group.text, &group.background) {
+        if let Some(ratio) = contrast(text, background) {
+            if ratio < MIN_CONTRAST {
+                warnings.push(format!(
+                    "low contrast between text and background of `{}` ({:.1}:1)",
+                    name, ratio
+                ));
+            }
+        }
+    }
+}
+
+// Finds things in a theme that are most likely mistakes, although the theme
+// can still be applied. `theme` is the result of loading `doc`.
+pub fn lint(doc: &Yaml, theme: &Theme) -> Vec<String> {
+    let mut warnings = Vec::new();
+
+    if theme.meta.description.is_none() {
+        warnings.push("`meta.description` is missing".to_string());
+    }
+    if theme.meta.variant.is_none() {
+        warnings.push("`meta.variant` is missing".to_string());
+    }
+
+    let mut specs = Vec::new();
+    collect_specs(&doc["colors"], &mut specs);
+    collect_specs(&doc["window_colors"], &mut specs);
+    collect_specs(&doc["bar_colors"], &mut specs);
+    collect_specs(&doc["status_colors"], &mut specs);
+    let used: Vec<&str> = specs.iter().flat_map(|s| words(s)).collect();
+    for name in keys(&doc["colors"]) {
+        if !used.contains(&name.as_str()) {
+            warnings.push(format!("color `{}` is never used", name));
+        }
+    }
+
+    if let Some(ref wc) = theme.window_colors {
+        check_contrast(&mut warnings, "window_colors.focused", &wc.focused);
+        check_contrast(&mut warnings, "window_colors.focused_inactive", &wc.focused_inactive);
+        check_contrast(&mut warnings, "window_colors.unfocused", &wc.unfocused);
+        check_contrast(&mut warnings, "window_colors.urgent", &wc.urgent);
+    }
+    if let Some(ref bc) = theme.bar_colors {
+        check_contrast(&mut warnings, "bar_colors.focused_workspace", &bc.focused_workspace);
+        check_contrast(&mut warnings, "bar_colors.active_workspace", &bc.active_workspace);
+        check_contrast(&mut warnings, "bar_colors.inactive_workspace", &bc.inactive_workspace);
+        check_contrast(&mut warnings, "bar_colors.urgent_workspace", &bc.urgent_workspace);
+    }
+
+    warnings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use theme::from_yaml;
+    use yaml_rust::YamlLoader;
+
+    #[test]
+    fn test_lint() {
+        let docs = YamlLoader::load_from_str(
+            "meta:
+  description: test
+colors:
+  black: '#000000'
+  white: '#ffffff'
+  gray: '#777777'
+  unused: '#123456'
+  dim: darken(gray, 10%)
+window_colors:
+  focused:
+    border: black
+    background: black
+    text: white
+  urgent:
+    background: gray
+    text: dim
+",
+        )
+        .unwrap();
+        let doc = &docs[0];
+        let warnings = lint(doc, &from_yaml(doc).unwrap());
+
+        assert_eq!(
+            warnings,
+            vec![
+                "`meta.variant` is missing",
+                "color `unused` is never used",
+                "low contrast between text and background of `window_colors.urgent` (1.4:1)",
+            ]
+        );
+    }
+}
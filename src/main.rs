@@ -1,368 +1,1238 @@
+extern crate i3_style;
 #[macro_use]
 extern crate lazy_static;
-extern crate linked_hash_map;
-extern crate palette;
 use std::env;
 use std::fs;
 use std::fs::create_dir_all;
-use std::fs::File;
 use std::io::prelude::*;
-use std::io::Error;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::process;
-use std::process::{Command, Stdio};
 use std::time::{SystemTime, UNIX_EPOCH};
-extern crate includedir;
-extern crate phf;
-
 extern crate yaml_rust;
-use yaml_rust::{YamlEmitter, YamlLoader};
+use yaml_rust::{Yaml, YamlLoader};
 
 extern crate clap;
-use clap::{App, Arg};
-
-mod theme;
-mod writer;
-mod filters;
+use clap::{App, Arg, ArgGroup, ArgMatches, SubCommand};
 
-use filters::{Filter, Filterable};
+use i3_style::{
+    ansi, base16, catalog, export, filters, format, gallery, ipc, lint, pick, render, settings,
+    status, theme, validate, wal, wallpaper, writer, xresources,
+};
+use i3_style::{read_theme_doc, Filterable};
+use catalog::ThemeSource;
 
-include!(concat!(env!("OUT_DIR"), "/data.rs"));
+extern crate crossterm;
+extern crate similar;
+use similar::TextDiff;
 
 fn exit_error(msg: &str) {
     writeln!(&mut std::io::stderr(), "{}", msg).unwrap();
     process::exit(1);
 }
 
+lazy_static! {
+    // The defaults from the team and user `config.toml`.
+    static ref SETTINGS: settings::Settings = match settings::load() {
+        Ok(s) => s,
+        Err(e) => {
+            exit_error(format!("Could not read settings: {}", e).as_str());
+            unreachable!()
+        }
+    };
+}
+
+fn get_backup_dir() -> PathBuf {
+    let mut dir = env::temp_dir();
+    dir.push("i3-style");
+    dir
+}
+
 fn get_run_tmp_dir() -> String {
     let start = SystemTime::now();
     let elapsed = start
         .duration_since(UNIX_EPOCH)
         .expect("Time went backwards");
-    let sec = (elapsed.as_secs() as u64) + (elapsed.subsec_nanos() as u64) / 1000_000;
-    let mut tmp_dir = env::temp_dir();
-    tmp_dir.push("i3-style");
+    let sec = elapsed.as_secs() + (elapsed.subsec_nanos() as u64) / 1_000_000;
+    let mut tmp_dir = get_backup_dir();
     tmp_dir.push(sec.to_string());
     create_dir_all(tmp_dir.as_path()).expect("Could not create temporary directory");
     String::from(tmp_dir.to_str().unwrap())
 }
 
-fn get_system_config_path() -> Option<String> {
-    let home = String::from(env::var("HOME").unwrap());
+// Removes all but the `keep` newest config backups.
+fn prune_backups(keep: usize) {
+    let mut runs: Vec<(SystemTime, PathBuf)> = match fs::read_dir(get_backup_dir()) {
+        Ok(entries) => entries
+            .filter_map(|e| e.ok())
+            .filter(|e| e.path().join("config-input").is_file())
+            .filter_map(|e| Some((e.metadata().ok()?.modified().ok()?, e.path())))
+            .collect(),
+        Err(_) => return,
+    };
+    runs.sort();
+    let stale = runs.len().saturating_sub(keep);
+    for (_, run) in runs.into_iter().take(stale) {
+        let _ = fs::remove_dir_all(run);
+    }
+}
+
+// The config given as `-` is read from stdin.
+const STDIN: &str = "-";
 
-    let config_path = vec![
-        format!("{}/{}", home, ".i3/config"),
-        format!("{}/{}", home, ".config/i3/config"),
-        String::from("/etc/i3/config"),
-        String::from("/etc/xdg/i3/config"),
-    ];
+fn read_config_or_exit(config: &str) -> String {
+    let contents = if config == STDIN {
+        let mut contents = String::new();
+        std::io::stdin().read_to_string(&mut contents).map(|_| contents)
+    } else {
+        fs::read_to_string(config)
+    };
 
-    for p in config_path {
-        if Path::new(&p).exists() {
-            return Some(p);
+    match contents {
+        Ok(c) => c,
+        Err(e) => {
+            exit_error(format!("Could not read config: {} - {}", config, e).as_str());
+            unreachable!()
         }
     }
+}
 
-    None
+fn validate_config_or_exit(path: &str, contents: &str) {
+    match validate::validate_config(path, contents) {
+        Ok(warnings) => {
+            for warning in warnings {
+                eprintln!("warning: {}", warning);
+            }
+        }
+        Err(err) => exit_error(format!("Could not validate config.\n{}", err).as_str()),
+    }
 }
 
-fn validate_config(path: &String) -> Result<bool, Error> {
-    let cmd = Command::new("i3")
-        .arg("-C")
-        .arg("-c")
-        .arg(path)
-        .stdout(Stdio::null())
-        .stderr(Stdio::null())
-        .status()?;
+fn get_embedded_theme(name: &str) -> Option<theme::Theme> {
+    let contents = i3_style::embedded_theme(name)?;
+    let doc = i3_style::parse_theme_doc(&contents, Path::new(name)).expect("Could not parse yaml for theme");
 
-    Ok(cmd.success())
+    match theme::from_yaml(&doc) {
+        Ok(t) => Some(t),
+        Err(e) => {
+            exit_error(format!("Could not load theme: {} - {}", name, e).as_str());
+            None
+        }
+    }
 }
 
-fn validate_config_or_exit(path: &String) {
-    match validate_config(&path) {
-        Ok(result) => {
-            if !result {
-                writeln!(
-                    &mut std::io::stderr(),
-                    "Could not validate config.\nUse `i3 -C -c {}` to see validation errors.",
-                    path
-                ).unwrap();
-                process::exit(1);
+fn load_theme_or_exit(name: &str) -> theme::Theme {
+    let theme = i3_style::load_theme(name);
+
+    match theme {
+        Ok(t) => t,
+        Err(e) => {
+            exit_error(format!("Could not open theme: {} - {}\n Use `i3-style list` to see the available themes.", name, e).as_str());
+            unreachable!()
+        }
+    }
+}
+
+const PALETTE_SOURCES: &[&str] = &["from-wal", "from-xresources"];
+
+// The colors pywal wrote for the current wallpaper or those of an
+// Xresources file as a theme, with the role mapping given on the command
+// line.
+fn palette_theme_doc(app: &ArgMatches) -> Result<Yaml, String> {
+    let roles = match app.value_of("roles") {
+        Some(r) => fs::read_to_string(r).map_err(|e| format!("{}: {}", r, e))?,
+        None => wal::DEFAULT_ROLES.to_string(),
+    };
+
+    if app.is_present("from-xresources") {
+        let path = match app.value_of("from-xresources") {
+            Some(p) => PathBuf::from(p),
+            None => xresources::default_path().ok_or("HOME is not set")?,
+        };
+        let contents = fs::read_to_string(&path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        let dir = path.parent().unwrap_or_else(|| Path::new("."));
+        let resources = xresources::resources(&contents, dir).map_err(|e| format!("{}: {}", path.display(), e))?;
+        let description = format!("Colors from {}", path.display());
+        return wal::with_roles(&xresources::palette(&resources), &roles, &description);
+    }
+
+    let path = match app.value_of("from-wal") {
+        Some(p) => PathBuf::from(p),
+        None => wal::default_path().ok_or("HOME is not set")?,
+    };
+    let contents = fs::read_to_string(&path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    wal::to_theme_doc(&contents, &roles).map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+fn uses_palette(app: &ArgMatches) -> bool {
+    PALETTE_SOURCES.iter().any(|a| app.is_present(a))
+}
+
+// Loads the selected theme with the overlays, filter and overrides given on
+// the command line applied to it.
+fn theme_from_args(app: &ArgMatches) -> theme::Theme {
+    if app.is_present("roles") && !uses_palette(app) {
+        exit_error("--roles needs --from-wal or --from-xresources");
+    }
+
+    let mut theme = if uses_palette(app) {
+        match palette_theme_doc(app).and_then(|doc| theme::from_yaml(&doc)) {
+            Ok(t) => t,
+            Err(e) => {
+                exit_error(format!("Could not load the colors: {}", e).as_str());
+                unreachable!()
             }
         }
-        Err(err) => {
-            eprintln!(
-                "Could not validate config with `i3 -C -c {}` (is i3 in the PATH?) {}",
-                path, err
-            );
-            process::exit(1);
+    } else {
+        load_theme_or_exit(app.value_of("theme").unwrap())
+    };
+
+    if let Some(overlays) = app.values_of("overlay") {
+        for overlay in overlays {
+            theme.merge(load_theme_or_exit(overlay));
         }
     }
+
+    let hue = match app.value_of("filter") {
+        Some(filter) => match filter.parse::<f32>() {
+            Ok(h) => Some(h),
+            Err(_) => {
+                exit_error(format!("Could not parse hue: {}", filter).as_str());
+                None
+            }
+        },
+        None => SETTINGS.filter,
+    };
+    if let Some(hue) = hue {
+        let filter = filters::HueFilter { hue, space: SETTINGS.color_space() };
+        theme.apply_filter(&filter);
+    }
+
+    if let Some(overrides) = app.values_of("set") {
+        for o in overrides {
+            let mut kv = o.splitn(2, '=');
+            let key = kv.next().unwrap();
+            let value = match kv.next() {
+                Some(v) => v,
+                None => {
+                    exit_error(format!("Could not parse override: {} (expected <key>=<color>)", o).as_str());
+                    ""
+                }
+            };
+            if let Err(e) = theme.set(key, value) {
+                exit_error(format!("Could not apply override {}: {}", o, e).as_str());
+            }
+        }
+    }
+
+    theme
 }
 
-fn get_embedded_theme(name: &str) -> Option<theme::Theme> {
-    let file = format!("./themes/{}", name);
+fn config_from_args(app: &ArgMatches) -> String {
+    let config = match app.value_of("config") {
+        Some(c) => Some(String::from(c)),
+        None => settings::i3_config(&SETTINGS).map(|p| p.to_string_lossy().to_string()),
+    };
 
-    if !FILES.is_available(&file) {
-        return None;
+    match config {
+        Some(c) => c,
+        None => {
+            exit_error("Could not find i3 config");
+            // not reached
+            String::from("")
+        }
     }
+}
 
-    let contents = String::from_utf8(
-        FILES
-            .get(&format!("{}/{}", "./themes", name))
-            .unwrap()
-            .to_vec(),
-    )
-    .expect("Theme yaml is not utf-8");
+fn theme_to_string(theme: theme::Theme, format: &str) -> String {
+    match format::emit(&theme, format) {
+        Ok(out) => out,
+        Err(e) => {
+            exit_error(format!("Could not write the theme as {}: {}", format, e).as_str());
+            unreachable!()
+        }
+    }
+}
 
-    let docs =
-        YamlLoader::load_from_str(contents.as_str()).expect("Could not parse yaml for theme");
-    let doc = &docs[0];
+// Imported documents are written as they are to YAML, which keeps the names
+// of their colors, and through the theme model to the other formats.
+fn doc_to_string(doc: &Yaml, format: &str) -> String {
+    let out = match format {
+        "yaml" => format::emit_yaml(doc),
+        _ => theme::from_yaml(doc).and_then(|theme| format::emit(&theme, format)),
+    };
+    match out {
+        Ok(out) => out,
+        Err(e) => {
+            exit_error(format!("Could not write the theme as {}: {}", format, e).as_str());
+            unreachable!()
+        }
+    }
+}
 
-    Some(theme::from_yaml(&doc))
+// The format to write a theme in, which is given with `--format` or else
+// by the extension of the output file.
+fn output_format<'a>(app: &'a ArgMatches) -> &'a str {
+    app.value_of("format")
+        .or_else(|| app.value_of("output").and_then(|o| format::from_path(Path::new(o))))
+        .unwrap_or("yaml")
 }
 
-fn get_theme_from_path(path: String) -> Result<theme::Theme, Error> {
-    let mut file = File::open(path)?;
+// Applies the theme to the contents of a config.
+fn render_config(contents: &str, theme: &theme::Theme) -> String {
+    let mut themed = Vec::new();
+    writer::write_theme(contents.as_bytes(), &mut themed, theme).expect("Could not apply the theme");
+    String::from_utf8(themed).expect("The themed config is not utf-8")
+}
 
-    let mut contents = String::new();
-    file.read_to_string(&mut contents)?;
+fn user_theme_dir_or_exit() -> PathBuf {
+    match catalog::user_theme_dir() {
+        Some(dir) => dir,
+        None => {
+            exit_error("Could not find the user theme directory (is HOME set?)");
+            unreachable!()
+        }
+    }
+}
 
-    let docs =
-        YamlLoader::load_from_str(contents.as_str()).expect("Could not parse yaml for theme");
-    let doc = &docs[0];
+fn install(app: &ArgMatches) {
+    let source = Path::new(app.value_of("source").unwrap());
+    let themes = match catalog::read_theme_sources(source) {
+        Ok(t) => t,
+        Err(e) => {
+            exit_error(format!("Could not install {}: {}", source.display(), e).as_str());
+            unreachable!()
+        }
+    };
+
+    if app.is_present("name") && themes.len() > 1 {
+        exit_error("--name can only be used when installing a single theme");
+    }
+
+    let dir = user_theme_dir_or_exit();
+    for (name, contents) in &themes {
+        let name = app.value_of("name").unwrap_or(name);
+        match catalog::install_theme(&dir, name, contents) {
+            Ok(path) => eprintln!("installed {} to {}", name, path.display()),
+            Err(e) => exit_error(format!("Could not install {}: {}", name, e).as_str()),
+        }
+    }
+}
+
+// Every theme that can be selected by name, sorted by name.
+fn all_themes() -> Vec<(String, Result<theme::Theme, String>, ThemeSource)> {
+    let mut themes: Vec<(String, Result<theme::Theme, String>, ThemeSource)> = Vec::new();
+
+    for (name, path) in catalog::user_themes() {
+        let theme = i3_style::theme_from_path(&path);
+        themes.push((name, theme, ThemeSource::User(path)));
+    }
+
+    for name in i3_style::embedded_theme_names() {
+        if themes.iter().any(|t| t.0 == name) {
+            continue;
+        }
+        let theme = get_embedded_theme(&name).unwrap();
+        themes.push((name, Ok(theme), ThemeSource::Embedded));
+    }
 
-    Ok(theme::from_yaml(&doc))
+    themes.sort_by(|a, b| a.0.cmp(&b.0));
+    themes
 }
 
-fn list_themes() {
+fn list_themes(variant: Option<theme::Variant>, tags: &[&str], favorites: Option<&[String]>) {
     println!("\nAvailable themes:\n");
-    let prefix_count = "./themes/".chars().count();
 
-    for file in FILES.file_names() {
-        let contents =
-            String::from_utf8(FILES.get(file).unwrap().to_vec()).expect("Theme yaml is not utf-8");
+    for (name, theme, source) in all_themes() {
+        let source = match source {
+            ThemeSource::Embedded => String::from("built-in"),
+            ThemeSource::User(path) => path.to_string_lossy().to_string(),
+        };
 
-        let docs =
-            YamlLoader::load_from_str(contents.as_str()).expect("Could not parse yaml for theme");
-        let doc = &docs[0];
+        let theme = match theme {
+            Ok(t) => t,
+            Err(e) => {
+                if variant.is_none() && tags.is_empty() && favorites.is_none() {
+                    println!("  {:width$} - could not load theme: {} [{}]", name, e, source, width = 18);
+                }
+                continue;
+            }
+        };
+        let meta = &theme.meta;
 
-        let description = doc["meta"]["description"]
-            .as_str()
-            .expect("Theme doesn't have a description");
+        if variant.is_some() && meta.variant != variant {
+            continue;
+        }
+        if !tags.iter().all(|tag| meta.tags.iter().any(|t| t == tag)) {
+            continue;
+        }
+        if favorites.is_some_and(|f| !f.contains(&name)) {
+            continue;
+        }
 
-        let entry: String = file.chars().skip(prefix_count).collect();
-        let entry = format!("  {:width$} - {}", entry, description, width = 18);
+        let mut entry = format!(
+            "  {:width$} - {}",
+            name,
+            meta.description.as_ref().map_or("", String::as_str),
+            width = 18
+        );
+        let mut details: Vec<String> = Vec::new();
+        if let Some(v) = meta.variant {
+            details.push(v.as_str().to_string());
+        }
+        if !meta.tags.is_empty() {
+            details.push(meta.tags.join(", "));
+        }
+        if !details.is_empty() {
+            entry.push_str(&format!(" ({})", details.join("; ")));
+        }
+        entry.push_str(&format!(" [{}]", source));
         println!("{}", entry);
     }
 }
 
-fn main() {
-    let mut cli = App::new("i3-style")
-        .version("1.0")
-        .about("Make your i3 config a bit more stylish")
-        .arg(Arg::with_name("theme")
-             .help("The theme to use")
-             .index(1)
-             )
-        .arg(Arg::with_name("config")
-            .short("c")
-            .long("config")
-            .value_name("file")
-            .help("The config file the theme should be applied to. Defaults to the default i3 location.")
-            .takes_value(true)
-            )
-        .arg(Arg::with_name("output")
-             .short("o")
-             .long("output")
-             .value_name("file")
-             .help("Apply the theme, attempt to validate the result, and write it to <file>")
-             .takes_value(true)
-            )
-        .arg(Arg::with_name("save")
-             .short("s")
-             .long("save")
-             .help("Set the output file to the path of the input file")
-            )
-        .arg(Arg::with_name("filter")
-            .short("f")
-            .long("filter")
-            .value_name("hue")
-            .help("Hue rotation from 0 to 360")
-            .takes_value(true)
-            )
-        .arg(Arg::with_name("reload")
-             .short("r")
-             .long("reload")
-             .help("Apply the theme by reloading the config")
-            )
-        .arg(Arg::with_name("list-all")
-             .short("l")
-             .long("list-all")
-             .help("Print a list of all available themes")
-            )
-        .arg(Arg::with_name("to-theme")
-             .short("t")
-             .long("to-theme")
-             .value_name("file")
-             .help("Prints an i3-style theme based on the given config suitable for sharing with others")
-             .takes_value(true)
-             .default_value("")
-            );
+fn reload() {
+    let result = ipc::Connection::connect_default().and_then(|mut c| c.run_command("reload"));
 
-    let app = cli.clone().get_matches();
+    if let Err(err) = result {
+        writeln!(
+            &mut std::io::stderr(),
+            "Could not reload config: {}",
+            err
+        ).unwrap();
+        process::exit(1);
+    }
+
+    writeln!(&mut std::io::stderr(), "config reloaded").unwrap();
+}
+
+fn apply_live(theme: &theme::Theme) {
+    let result = ipc::Connection::connect_default().and_then(|mut c| c.apply_theme(theme));
+
+    if let Err(err) = result {
+        exit_error(format!("Could not apply the theme: {}", err).as_str());
+    }
+}
+
+fn apply(app: &ArgMatches) {
+    apply_theme(app, &theme_from_args(app));
+}
+
+// `apply` and `pick` need to be told where the theme goes, unless the
+// settings say to write in place.
+fn require_destination(app: &ArgMatches) {
+    let given = ["output", "in-place", "live"].iter().any(|a| app.is_present(a));
+    if !given && !SETTINGS.in_place() {
+        exit_error("Give --output, --in-place or --live, or set `in_place = true` in the settings");
+    }
+}
+
+// Applies the theme where the arguments say, which is over IPC, to a config
+// file or to stdout.
+fn apply_theme(app: &ArgMatches, theme: &theme::Theme) {
+    if app.is_present("live") {
+        apply_live(theme);
+
+        if !app.is_present("output") && !app.is_present("in-place") {
+            eprintln!("theme applied until the next reload, use `--in-place` to keep it");
+            return;
+        }
+    }
+
+    let config = config_from_args(app);
+    let contents = read_config_or_exit(&config);
+
+    validate_config_or_exit(&config, &contents);
+
+    let output = if app.value_of("output").is_some() {
+        app.value_of("output")
+    } else if app.is_present("in-place") || app.is_present("save") {
+        if config == STDIN {
+            exit_error("A config read from stdin can't be written in place, use --output");
+        }
+        Some(config.as_str())
+    } else if SETTINGS.in_place() && config != STDIN {
+        Some(config.as_str())
+    } else {
+        None
+    };
+
+    let themed = render_config(&contents, theme);
+    if let Some(output) = output {
+        // keep a copy of the config in case the result isn't what was wanted
+        let backups = SETTINGS.backups;
+        if config != STDIN && backups != Some(0) {
+            let tmp_input = format!("{}/{}", get_run_tmp_dir(), "config-input");
+            writeln!(
+                &mut std::io::stderr(),
+                "saving config at {} to {}",
+                &config,
+                &tmp_input
+            ).unwrap();
+            fs::write(&tmp_input, &contents).unwrap();
+            if let Some(keep) = backups {
+                prune_backups(keep);
+            }
+        }
+        if let Err(e) = fs::write(output, themed) {
+            exit_error(format!("Could not write {}: {}", output, e).as_str());
+        }
+    } else {
+        print!("{}", themed);
+    }
+
+    if app.is_present("reload") || (SETTINGS.reload() && output.is_some()) {
+        reload();
+    }
+}
+
+fn pick(app: &ArgMatches) {
+    let mut themes: Vec<(String, theme::Theme)> = all_themes()
+        .into_iter()
+        .filter_map(|(name, theme, _)| theme.ok().map(|t| (name, t)))
+        .collect();
+    // favorites come first, in the order they are listed
+    let favorites = SETTINGS.favorites();
+    themes.sort_by_key(|(name, _)| favorites.iter().position(|f| f == name).unwrap_or(favorites.len()));
 
-    if app.is_present("list-all") {
-        list_themes();
-        process::exit(0);
-    }
-
-    if app.occurrences_of("to-theme") > 0 {
-        let mut config: String = String::from(app.value_of("to-theme").unwrap());
-
-        if config.is_empty() {
-            config = match app.value_of("config") {
-                Some(c) => String::from(c),
-                None => match get_system_config_path() {
-                    Some(c) => c,
-                    None => {
-                        exit_error("Could not find i3 config");
-                        // not reached
-                        String::from("")
-                    }
-                },
+    let picked = match pick::pick(&themes, SETTINGS.color_space()) {
+        Ok(Some(p)) => p,
+        Ok(None) => return,
+        Err(e) => {
+            exit_error(format!("Could not pick a theme: {}", e).as_str());
+            unreachable!()
+        }
+    };
+
+    let (ref name, ref theme) = themes[picked.index];
+    let mut theme = theme.clone();
+    if picked.hue != 0 {
+        theme.apply_filter(&filters::HueFilter { hue: picked.hue as f32, space: SETTINGS.color_space() });
+        eprintln!("applying {} with the hue rotated by {}°", name, picked.hue);
+    } else {
+        eprintln!("applying {}", name);
+    }
+
+    apply_theme(app, &theme);
+}
+
+fn list(app: &ArgMatches) {
+    let variant = app.value_of("variant").and_then(theme::Variant::from_name);
+    let tags: Vec<&str> = match app.values_of("tag") {
+        Some(tags) => tags.collect(),
+        None => Vec::new(),
+    };
+    let favorites = if app.is_present("favorites") {
+        Some(SETTINGS.favorites())
+    } else {
+        None
+    };
+    list_themes(variant, &tags, favorites);
+}
+
+fn show(app: &ArgMatches) {
+    let theme = theme_from_args(app);
+
+    if app.is_present("yaml") || app.is_present("format") {
+        println!("{}", theme_to_string(theme, output_format(app)));
+        return;
+    }
+
+    let width = match app.value_of("width") {
+        Some(w) => match w.parse::<usize>() {
+            Ok(w) => w,
+            Err(_) => {
+                exit_error(format!("Could not parse width: {}", w).as_str());
+                unreachable!()
             }
+        },
+        None => crossterm::terminal::size().map_or(80, |s| s.0 as usize),
+    };
+
+    if let Some(ref description) = theme.meta.description {
+        println!("{}\n", description);
+    }
+    for line in ansi::screen(&theme, width) {
+        println!("{}", line);
+    }
+}
+
+fn render(app: &ArgMatches) {
+    let theme = theme_from_args(app);
+    let output = app.value_of("output").unwrap();
+
+    let contents = if output.ends_with(".svg") {
+        Ok(render::svg(&theme).into_bytes())
+    } else if output.ends_with(".png") {
+        render::png(&theme)
+    } else {
+        Err(String::from("the output file has to end in .svg or .png"))
+    };
+
+    let result = contents.and_then(|c| fs::write(output, c).map_err(|e| e.to_string()));
+    if let Err(e) = result {
+        exit_error(format!("Could not render {}: {}", output, e).as_str());
+    }
+}
+
+fn gallery(app: &ArgMatches) {
+    let mut themes = Vec::new();
+    for (name, theme, _) in all_themes() {
+        match theme {
+            Ok(t) => themes.push((name, t)),
+            Err(e) => eprintln!("skipping {}: {}", name, e),
         }
+    }
+
+    let dir = Path::new(app.value_of("output").unwrap());
+    if let Err(e) = gallery::write(dir, &themes) {
+        exit_error(format!("Could not write the gallery: {}", e).as_str());
+    }
+    eprintln!("wrote {} themes to {}", themes.len(), dir.join("index.html").display());
+}
 
-        validate_config_or_exit(&config);
+fn from_image(app: &ArgMatches) {
+    let image = Path::new(app.value_of("image").unwrap());
+    let k = match app.value_of("colors").unwrap().parse::<usize>() {
+        Ok(k) if k > 0 => k,
+        _ => {
+            exit_error("--colors has to be a positive number");
+            unreachable!()
+        }
+    };
 
-        let theme = theme::from_config_file(&config);
+    let theme = match wallpaper::theme_from_image(image, k) {
+        Ok(t) => t,
+        Err(e) => {
+            exit_error(format!("Could not read {}: {}", image.display(), e).as_str());
+            unreachable!()
+        }
+    };
 
-        let yaml = theme.to_yaml_with_colors();
+    let out = theme_to_string(theme, output_format(app));
+    match app.value_of("output") {
+        Some(output) => {
+            if let Err(e) = fs::write(output, out + "\n") {
+                exit_error(format!("Could not write {}: {}", output, e).as_str());
+            }
+        }
+        None => println!("{}", out),
+    }
+}
 
-        let mut out_str = String::new();
-        {
-            let mut emitter = YamlEmitter::new(&mut out_str);
-            emitter.dump(&yaml).unwrap(); // dump the YAML object to a String
+fn import(app: &ArgMatches) {
+    let doc = match app.value_of("base16") {
+        Some(path) => fs::read_to_string(path)
+            .map_err(|e| e.to_string())
+            .and_then(|contents| YamlLoader::load_from_str(&contents).map_err(|e| e.to_string()))
+            .and_then(|docs| match docs.first() {
+                Some(doc) if base16::is_scheme(doc) => base16::to_theme_doc(doc),
+                _ => Err("not a base16 scheme".to_string()),
+            })
+            .map_err(|e| format!("{}: {}", path, e)),
+        None => palette_theme_doc(app),
+    };
+    let doc = match doc {
+        Ok(doc) => doc,
+        Err(e) => {
+            exit_error(format!("Could not import {}", e).as_str());
+            unreachable!()
         }
-        println!("{}", out_str);
+    };
 
-        process::exit(0);
+    let out = doc_to_string(&doc, output_format(app));
+    match app.value_of("output") {
+        Some(output) => {
+            if let Err(e) = fs::write(output, out + "\n") {
+                exit_error(format!("Could not write {}: {}", output, e).as_str());
+            }
+        }
+        None => println!("{}", out),
     }
+}
 
-    if !app.is_present("theme") {
-        if app.args.is_empty() {
-            cli.print_help().unwrap();
-            process::exit(0);
-        } else {
-            println!("{}\n", app.usage());
-            println!("Select a theme as the first argument. Use `--list-all` to see the themes.");
-            process::exit(1);
+fn export(app: &ArgMatches) {
+    let theme = theme_from_args(app);
+    let out = match export::export(&theme, app.value_of("format").unwrap()) {
+        Ok(out) => out,
+        Err(e) => {
+            exit_error(format!("Could not export the theme: {}", e).as_str());
+            unreachable!()
         }
+    };
+
+    match app.value_of("output") {
+        Some(output) => {
+            if let Err(e) = fs::write(output, out) {
+                exit_error(format!("Could not write {}: {}", output, e).as_str());
+            }
+        }
+        None => print!("{}", out),
     }
+}
+
+fn status(app: &ArgMatches) {
+    let theme = theme_from_args(app);
+    let format = app.value_of("format").unwrap();
+    let colors = match theme.status_colors {
+        Some(ref c) => c,
+        None => {
+            exit_error("The theme has no status_colors");
+            unreachable!()
+        }
+    };
 
     let config = match app.value_of("config") {
-        Some(c) => Some(String::from(c)),
-        None => get_system_config_path(),
+        Some(c) => PathBuf::from(c),
+        None => match status::default_config(format) {
+            Some(c) => c,
+            None => {
+                exit_error(format!("Could not find the {} config", format).as_str());
+                unreachable!()
+            }
+        },
+    };
+    let contents = read_config_or_exit(&config.to_string_lossy());
+    let result = match status::write(format, &contents, colors) {
+        Ok(r) => r,
+        Err(e) => {
+            exit_error(format!("Could not read config: {} - {}", config.display(), e).as_str());
+            unreachable!()
+        }
     };
 
-    if config.is_none() {
-        exit_error("Could not find i3 config");
+    let output = match app.value_of("output") {
+        Some(o) => Some(PathBuf::from(o)),
+        None if app.is_present("in-place") => {
+            if config == Path::new(STDIN) {
+                exit_error("A config read from stdin can't be written in place, use --output");
+            }
+            Some(config)
+        }
+        None => None,
+    };
+    match output {
+        Some(output) => {
+            if let Err(e) = fs::write(&output, result) {
+                exit_error(format!("Could not write {}: {}", output.display(), e).as_str());
+            }
+        }
+        None => print!("{}", result),
     }
-    let config = config.unwrap();
+}
 
-    validate_config_or_exit(&config);
+fn extract(config: String, format: &str) {
+    let contents = read_config_or_exit(&config);
+    validate_config_or_exit(&config, &contents);
 
-    let theme_name = app.value_of("theme").unwrap();
-    let mut theme: Option<theme::Theme> = get_embedded_theme(theme_name);
+    let theme = theme::from_config(contents.as_bytes()).expect("Could not read the config");
+    println!("{}", theme_to_string(theme, format));
+}
 
-    if theme.is_none() {
-        // TODO get theme from the filesystem
-        theme = match get_theme_from_path(theme_name.to_string()) {
-            Ok(t) => Some(t),
+fn diff(app: &ArgMatches) {
+    let config = config_from_args(app);
+    let theme = theme_from_args(app);
+
+    let original = read_config_or_exit(&config);
+    let themed = render_config(&original, &theme);
+
+    let diff = TextDiff::from_lines(&original, &themed);
+    let themed_name = format!("{} ({})", config, app.value_of("theme").unwrap_or("colors"));
+    print!(
+        "{}",
+        diff.unified_diff().context_radius(3).header(&config, &themed_name)
+    );
+}
+
+fn lint(app: &ArgMatches) {
+    let mut failed = false;
+
+    for name in app.values_of("theme").unwrap() {
+        let doc = match read_theme_doc(name) {
+            Ok(doc) => doc,
             Err(e) => {
-                exit_error(format!("Could not open theme: {} - {}\n Use `i3-style --list-all` to see the available themes.", theme_name, e).as_str());
-                None
+                println!("{}: error: {}", name, e);
+                failed = true;
+                continue;
             }
+        };
+        let theme = match theme::from_yaml(&doc) {
+            Ok(t) => t,
+            Err(e) => {
+                println!("{}: error: {}", name, e);
+                failed = true;
+                continue;
+            }
+        };
+
+        let warnings = lint::lint(&doc, &theme);
+        for warning in &warnings {
+            println!("{}: warning: {}", name, warning);
+        }
+        if app.is_present("strict") && !warnings.is_empty() {
+            failed = true;
         }
     }
-    let mut theme = theme.unwrap();
 
-    if let Some(filter) = app.value_of("filter") {
-        let hue = filter.parse::<f32>().unwrap();
-        let filter = filters::HueFilter{ hue };
-        theme.apply_filter(&filter);
+    if failed {
+        process::exit(1);
     }
+}
 
-    let output = if app.value_of("output").is_some() {
-        app.value_of("output")
-    } else if app.is_present("save") {
-        Some(config.as_str().clone())
-    } else {
-        None
-    };
+fn preview(app: &ArgMatches) {
+    let config = config_from_args(app);
+    let theme = theme_from_args(app);
+    print!("{}", render_config(&read_config_or_exit(&config), &theme));
+}
 
-    if output.is_some() {
-        let output = output.unwrap();
-        let i3_style_tmp = get_run_tmp_dir();
-        let tmp_output = format!("{}/{}", i3_style_tmp, "config-output");
-        let tmp_input = format!("{}/{}", i3_style_tmp, "config-input");
-        // 1. write the new config in the tmp folder
-        writer::write_config(&config, Some(&tmp_output), &theme);
-        // 2. copy the config to the tmp folder
-        writeln!(
-            &mut std::io::stderr(),
-            "saving config at {} to {}",
-            &config,
-            &tmp_input
-        ).unwrap();
-        fs::copy(&config, &tmp_input).unwrap();
-        // 3. copy the new config to the config location
-        fs::copy(&tmp_output, output).unwrap();
-    } else {
-        writer::write_config(&config, None, &theme);
-    }
+fn theme_arg<'a, 'b>() -> Arg<'a, 'b> {
+    Arg::with_name("theme")
+        .help("The theme to use")
+        .required(true)
+        .index(1)
+}
 
-    if app.is_present("reload") {
-        if env::var_os("WAYLAND_DISPLAY").is_some() {
-            let cmd = Command::new("swaymsg")
-                .arg("reload")
-                .stdout(Stdio::null())
-                .stderr(Stdio::null())
-                .status();
+fn palette_args<'a, 'b>() -> Vec<Arg<'a, 'b>> {
+    vec![
+        Arg::with_name("from-wal")
+            .long("from-wal")
+            .value_name("file")
+            .help("Use the colors.json written by pywal or wallust instead of a theme. Defaults to ~/.cache/wal/colors.json.")
+            .takes_value(true)
+            .min_values(0)
+            .max_values(1)
+            .conflicts_with("from-xresources"),
+        Arg::with_name("from-xresources")
+            .long("from-xresources")
+            .value_name("file")
+            .help("Use the terminal colors and i3wm resources of an Xresources file instead of a theme. Defaults to ~/.Xresources.")
+            .takes_value(true)
+            .min_values(0)
+            .max_values(1),
+        Arg::with_name("roles")
+            .long("roles")
+            .alias("wal-roles")
+            .value_name("file")
+            .help("A theme that uses the terminal color names (background, foreground, color0 to color15) to map them to roles")
+            .takes_value(true),
+    ]
+}
 
-            match cmd {
-                Ok(_) => {
-                    // nop
-                }
-                Err(err) => {
-                    writeln!(
-                        &mut std::io::stderr(),
-                        "Could not reload config with swaymsg: {}",
-                        err
-                    ).unwrap();
-                    process::exit(1);
-                }
+// The theme argument together with the palettes that can replace it.
+fn theme_source_args<'a, 'b>() -> Vec<Arg<'a, 'b>> {
+    let mut args = vec![theme_arg()
+        .required_unless_one(PALETTE_SOURCES)
+        .conflicts_with_all(PALETTE_SOURCES)];
+    args.extend(palette_args());
+    args
+}
+
+fn theme_format_arg<'a, 'b>() -> Arg<'a, 'b> {
+    Arg::with_name("format")
+        .long("format")
+        .value_name("format")
+        .help("The format to write the theme in. Defaults to the extension of the output file, or YAML.")
+        .takes_value(true)
+        .possible_values(format::FORMATS)
+}
+
+fn config_arg<'a, 'b>() -> Arg<'a, 'b> {
+    Arg::with_name("config")
+        .short("c")
+        .long("config")
+        .value_name("file")
+        .help("The config file the theme should be applied to, or - for stdin. Defaults to the default i3 location.")
+        .takes_value(true)
+}
+
+fn output_arg<'a, 'b>() -> Arg<'a, 'b> {
+    Arg::with_name("output")
+        .short("o")
+        .long("output")
+        .value_name("file")
+        .help("Apply the theme, attempt to validate the result, and write it to <file>")
+        .takes_value(true)
+}
+
+// Where `apply` and `pick` put the theme.
+fn destination_args<'a, 'b>() -> Vec<Arg<'a, 'b>> {
+    vec![
+        output_arg().conflicts_with("in-place"),
+        Arg::with_name("in-place")
+            .short("i")
+            .long("in-place")
+            .help("Write the result back to the config file"),
+        Arg::with_name("live")
+            .long("live")
+            .help("Set the colors of the running session over IPC without touching the config. Needs sway."),
+    ]
+}
+
+fn destination_group() -> ArgGroup<'static> {
+    ArgGroup::with_name("destination")
+        .args(&["output", "in-place", "live"])
+        .multiple(true)
+}
+
+fn reload_arg<'a, 'b>() -> Arg<'a, 'b> {
+    Arg::with_name("reload")
+        .short("r")
+        .long("reload")
+        .help("Apply the theme by reloading the config")
+}
+
+// The options that change the selected theme before it is used.
+fn theme_modifier_args<'a, 'b>() -> Vec<Arg<'a, 'b>> {
+    vec![
+        Arg::with_name("filter")
+            .short("f")
+            .long("filter")
+            .value_name("hue")
+            .help("Hue rotation from 0 to 360")
+            .takes_value(true),
+        Arg::with_name("overlay")
+            .long("overlay")
+            .value_name("theme")
+            .help("Layer another (possibly partial) theme on top of the selected one. Can be given multiple times.")
+            .takes_value(true)
+            .multiple(true)
+            .number_of_values(1),
+        Arg::with_name("set")
+            .long("set")
+            .value_name("key=color")
+            .help("Override a single color, e.g. `window_colors.urgent.border=#ff0000`. Applied after overlays and filters.")
+            .takes_value(true)
+            .multiple(true)
+            .number_of_values(1),
+    ]
+}
+
+fn list_filter_args<'a, 'b>() -> Vec<Arg<'a, 'b>> {
+    vec![
+        Arg::with_name("variant")
+            .long("variant")
+            .value_name("variant")
+            .help("Only list themes of this variant")
+            .takes_value(true)
+            .possible_values(&["dark", "light"]),
+        Arg::with_name("tag")
+            .long("tag")
+            .value_name("tag")
+            .help("Only list themes with this tag. Can be given multiple times.")
+            .takes_value(true)
+            .multiple(true)
+            .number_of_values(1),
+        Arg::with_name("favorites")
+            .long("favorites")
+            .help("Only list the favorite themes from the settings"),
+    ]
+}
+
+fn main() {
+    // The flags on the top level are the interface from before there were
+    // subcommands. They are kept working and map onto the subcommands below.
+    let mut cli = App::new("i3-style")
+        .version("1.0")
+        .about("Make your i3 config a bit more stylish")
+        .arg(Arg::with_name("theme")
+             .help("The theme to use")
+             .index(1)
+             )
+        .args(&palette_args())
+        .arg(config_arg())
+        .arg(output_arg())
+        .arg(Arg::with_name("save")
+             .short("s")
+             .long("save")
+             .help("Set the output file to the path of the input file")
+            )
+        .args(&theme_modifier_args())
+        .arg(reload_arg())
+        .arg(Arg::with_name("list-all")
+             .short("l")
+             .long("list-all")
+             .help("Print a list of all available themes")
+            )
+        .args(&list_filter_args()
+              .into_iter()
+              .map(|a| a.requires("list-all"))
+              .collect::<Vec<_>>())
+        .arg(Arg::with_name("to-theme")
+             .short("t")
+             .long("to-theme")
+             .value_name("file")
+             .help("Prints an i3-style theme based on the given config suitable for sharing with others")
+             .takes_value(true)
+             .default_value("")
+            )
+        .subcommand(SubCommand::with_name("apply")
+            .about("Apply a theme to the config and write the result")
+            .args(&theme_source_args())
+            .arg(config_arg())
+            .args(&destination_args())
+            .group(destination_group())
+            .args(&theme_modifier_args())
+            .arg(reload_arg())
+            )
+        .subcommand(SubCommand::with_name("pick")
+            .about("Browse the themes with a preview of their colors and apply one")
+            .arg(config_arg())
+            .args(&destination_args())
+            .group(destination_group())
+            .arg(reload_arg())
+            )
+        .subcommand(SubCommand::with_name("list")
+            .about("Print a list of all available themes")
+            .args(&list_filter_args())
+            )
+        .subcommand(SubCommand::with_name("show")
+            .about("Draw the window decorations and bar of a theme in the terminal")
+            .args(&theme_source_args())
+            .args(&theme_modifier_args())
+            .arg(Arg::with_name("yaml")
+                 .long("yaml")
+                 .help("Print the theme after resolving its colors, overlays and overrides instead")
+                )
+            .arg(theme_format_arg()
+                 .help("Like --yaml, but print the theme in this format")
+                )
+            .arg(Arg::with_name("width")
+                 .short("w")
+                 .long("width")
+                 .value_name("columns")
+                 .help("The width of the drawing. Defaults to the width of the terminal.")
+                 .takes_value(true)
+                )
+            )
+        .subcommand(SubCommand::with_name("render")
+            .about("Draw a desktop in the colors of a theme to an SVG or PNG file")
+            .args(&theme_source_args())
+            .arg(Arg::with_name("output")
+                 .short("o")
+                 .long("output")
+                 .value_name("file")
+                 .help("The file to write, ending in .svg or .png")
+                 .takes_value(true)
+                 .required(true)
+                )
+            .args(&theme_modifier_args())
+            )
+        .subcommand(SubCommand::with_name("gallery")
+            .about("Write an HTML page with a preview of every available theme")
+            .arg(Arg::with_name("output")
+                 .short("o")
+                 .long("output")
+                 .value_name("dir")
+                 .help("The directory to write the page and previews to")
+                 .takes_value(true)
+                 .required(true)
+                )
+            )
+        .subcommand(SubCommand::with_name("from-image")
+            .about("Make a theme from the colors of an image, like your wallpaper")
+            .arg(Arg::with_name("image")
+                 .help("A PNG or JPEG image")
+                 .required(true)
+                 .index(1)
+                )
+            .arg(Arg::with_name("output")
+                 .short("o")
+                 .long("output")
+                 .value_name("file")
+                 .help("Write the theme to <file> instead of printing it")
+                 .takes_value(true)
+                )
+            .arg(Arg::with_name("colors")
+                 .long("colors")
+                 .value_name("count")
+                 .help("How many colors to pick from the image")
+                 .takes_value(true)
+                 .default_value("8")
+                )
+            .arg(theme_format_arg())
+            )
+        .subcommand(SubCommand::with_name("import")
+            .about("Convert a color scheme from another format to an i3-style theme")
+            .arg(Arg::with_name("base16")
+                 .long("base16")
+                 .value_name("file")
+                 .help("A base16 scheme")
+                 .takes_value(true)
+                )
+            .args(&palette_args())
+            .group(ArgGroup::with_name("source")
+                   .args(&["base16", "from-wal", "from-xresources"])
+                   .required(true)
+                  )
+            .arg(Arg::with_name("output")
+                 .short("o")
+                 .long("output")
+                 .value_name("file")
+                 .help("Write the theme to <file> instead of printing it")
+                 .takes_value(true)
+                )
+            .arg(theme_format_arg())
+            )
+        .subcommand(SubCommand::with_name("export")
+            .about("Write the colors of a theme for Xresources, terminals, rofi or dunst")
+            .args(&theme_source_args())
+            .args(&theme_modifier_args())
+            .arg(Arg::with_name("format")
+                 .long("format")
+                 .value_name("format")
+                 .help("The program to write the colors for")
+                 .takes_value(true)
+                 .required(true)
+                 .possible_values(export::FORMATS)
+                )
+            .arg(Arg::with_name("output")
+                 .short("o")
+                 .long("output")
+                 .value_name("file")
+                 .help("Write the colors to <file> instead of printing them")
+                 .takes_value(true)
+                )
+            )
+        .subcommand(SubCommand::with_name("status")
+            .about("Set the good, degraded and bad colors of i3status, i3status-rust or i3blocks")
+            .args(&theme_source_args())
+            .args(&theme_modifier_args())
+            .arg(Arg::with_name("format")
+                 .long("format")
+                 .value_name("command")
+                 .help("The status command the config is for")
+                 .takes_value(true)
+                 .required(true)
+                 .possible_values(status::FORMATS)
+                )
+            .arg(Arg::with_name("config")
+                 .short("c")
+                 .long("config")
+                 .value_name("file")
+                 .help("The config of the status command, or - for stdin. Defaults to where it looks for it.")
+                 .takes_value(true)
+                )
+            .arg(Arg::with_name("output")
+                 .short("o")
+                 .long("output")
+                 .value_name("file")
+                 .help("Write the result to <file> instead of printing it")
+                 .takes_value(true)
+                )
+            .arg(Arg::with_name("in-place")
+                 .short("i")
+                 .long("in-place")
+                 .help("Write the result back to the config file")
+                 .conflicts_with("output")
+                )
+            )
+        .subcommand(SubCommand::with_name("extract")
+            .about("Print an i3-style theme based on a config suitable for sharing with others")
+            .arg(Arg::with_name("config")
+                 .help("The config to extract the theme from, or - for stdin. Defaults to the default i3 location.")
+                 .index(1)
+                )
+            .arg(theme_format_arg()
+                 .help("The format to write the theme in. Defaults to YAML.")
+                )
+            )
+        .subcommand(SubCommand::with_name("diff")
+            .about("Show the changes applying a theme would make to the config")
+            .args(&theme_source_args())
+            .arg(config_arg())
+            .args(&theme_modifier_args())
+            )
+        .subcommand(SubCommand::with_name("lint")
+            .about("Check themes for mistakes")
+            .arg(Arg::with_name("theme")
+                 .help("The themes to check")
+                 .required(true)
+                 .multiple(true)
+                 .index(1)
+                )
+            .arg(Arg::with_name("strict")
+                 .long("strict")
+                 .help("Fail on warnings as well as errors")
+                )
+            )
+        .subcommand(SubCommand::with_name("preview")
+            .about("Print the config with the theme applied without writing it")
+            .args(&theme_source_args())
+            .arg(config_arg())
+            .args(&theme_modifier_args())
+            )
+        .subcommand(SubCommand::with_name("install")
+            .about("Validate themes and copy them into the user theme directory")
+            .arg(Arg::with_name("source")
+                 .help("A theme file, a directory of themes or a .tar.gz of themes")
+                 .required(true)
+                 .index(1)
+                 )
+            .arg(Arg::with_name("name")
+                 .short("n")
+                 .long("name")
+                 .value_name("name")
+                 .help("Install a single theme under this name")
+                 .takes_value(true)
+                 )
+            )
+        .subcommand(SubCommand::with_name("uninstall")
+            .about("Remove a theme from the user theme directory")
+            .arg(Arg::with_name("name")
+                 .help("The name of the theme")
+                 .required(true)
+                 .index(1)
+                 )
+            );
+
+    let app = cli.clone().get_matches();
+
+    match app.subcommand() {
+        ("apply", Some(sub)) => {
+            require_destination(sub);
+            apply(sub)
+        }
+        ("list", Some(sub)) => list(sub),
+        ("pick", Some(sub)) => {
+            require_destination(sub);
+            pick(sub)
+        }
+        ("show", Some(sub)) => show(sub),
+        ("render", Some(sub)) => render(sub),
+        ("gallery", Some(sub)) => gallery(sub),
+        ("from-image", Some(sub)) => from_image(sub),
+        ("import", Some(sub)) => import(sub),
+        ("export", Some(sub)) => export(sub),
+        ("status", Some(sub)) => status(sub),
+        ("extract", Some(sub)) => extract(config_from_args(sub), output_format(sub)),
+        ("diff", Some(sub)) => diff(sub),
+        ("lint", Some(sub)) => lint(sub),
+        ("preview", Some(sub)) => preview(sub),
+        ("install", Some(sub)) => install(sub),
+        ("uninstall", Some(sub)) => {
+            let dir = user_theme_dir_or_exit();
+            match catalog::uninstall_theme(&dir, sub.value_of("name").unwrap()) {
+                Ok(path) => eprintln!("removed {}", path.display()),
+                Err(e) => exit_error(format!("Could not uninstall theme: {}", e).as_str()),
             }
-        } else {
-            let cmd = Command::new("i3-msg")
-                .arg("reload")
-                .stdout(Stdio::null())
-                .stderr(Stdio::null())
-                .status();
-
-            match cmd {
-                Ok(_) => {
-                    // nop
-                }
-                Err(err) => {
-                    writeln!(
-                        &mut std::io::stderr(),
-                        "Could not reload config with i3-msg: {}",
-                        err
-                    ).unwrap();
-                    process::exit(1);
+        }
+        _ => {
+            if app.is_present("list-all") {
+                list(&app);
+            } else if app.occurrences_of("to-theme") > 0 {
+                let config = app.value_of("to-theme").unwrap();
+                if config.is_empty() {
+                    extract(config_from_args(&app), "yaml");
+                } else {
+                    extract(String::from(config), "yaml");
                 }
+            } else if app.is_present("theme") || uses_palette(&app) {
+                apply(&app);
+            } else if app.args.is_empty() {
+                cli.print_help().unwrap();
+            } else {
+                println!("{}\n", app.usage());
+                println!("Select a theme as the first argument. Use `i3-style list` to see the themes.");
+                process::exit(1);
             }
         }
     }
@@ -0,0 +1,188 @@
+extern crate crossterm;
+
+use self::crossterm::cursor;
+use self::crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use self::crossterm::style::Print;
+use self::crossterm::terminal::{self, ClearType};
+use self::crossterm::{execute, queue};
+use std::io;
+use std::io::prelude::*;
+
+use ansi;
+use filters::{ColorSpace, Filterable, HueFilter};
+use theme::Theme;
+
+const HUE_STEP: i32 = 10;
+
+pub struct Picked {
+    pub index: usize,
+    pub hue: i32,
+}
+
+struct State<'a> {
+    themes: &'a [(String, Theme)],
+    selected: usize,
+    offset: usize,
+    hue: i32,
+    space: ColorSpace,
+    adjusting_filter: bool,
+}
+
+impl<'a> State<'a> {
+    fn preview(&self) -> Theme {
+        let mut theme = self.themes[self.selected].1.clone();
+        if self.hue != 0 {
+            theme.apply_filter(&HueFilter { hue: self.hue as f32, space: self.space });
+        }
+        theme
+    }
+
+    fn select(&mut self, index: usize) {
+        self.selected = index.min(self.themes.len() - 1);
+    }
+
+    fn draw(&mut self, out: &mut impl Write) -> io::Result<()> {
+        let (columns, rows) = terminal::size()?;
+        let (columns, rows) = (columns as usize, rows as usize);
+        let list_height = rows.saturating_sub(2).max(1);
+
+        // keep the selection in view
+        if self.selected < self.offset {
+            self.offset = self.selected;
+        } else if self.selected >= self.offset + list_height {
+            self.offset = self.selected + 1 - list_height;
+        }
+
+        let list_width = self.themes.iter().map(|t| t.0.len()).max().unwrap_or(0) + 4;
+        let preview_width = columns.saturating_sub(list_width + 2).max(20);
+
+        queue!(out, terminal::Clear(ClearType::All))?;
+
+        for (row, (name, _)) in self
+            .themes
+            .iter()
+            .enumerate()
+            .skip(self.offset)
+            .take(list_height)
+        {
+            let line = if row == self.selected {
+                format!("\x1b[7m > {:width$}{}", name, ansi::RESET, width = list_width - 3)
+            } else {
+                format!("   {}", name)
+            };
+            queue!(out, cursor::MoveTo(0, (row - self.offset) as u16), Print(line))?;
+        }
+
+        let theme = self.preview();
+        let mut lines = Vec::new();
+        lines.push(self.themes[self.selected].0.clone());
+        if let Some(ref description) = theme.meta.description {
+            lines.push(description.clone());
+        }
+        lines.push(String::new());
+        lines.extend(ansi::swatch(&theme, preview_width));
+        lines.push(String::new());
+        lines.push(if self.adjusting_filter {
+            format!("hue: {:+}°  (←/→ to change, 0 to reset, f when done)", self.hue)
+        } else if self.hue != 0 {
+            format!("hue: {:+}°", self.hue)
+        } else {
+            String::new()
+        });
+
+        for (row, line) in lines.iter().enumerate().take(list_height) {
+            queue!(out, cursor::MoveTo((list_width + 2) as u16, row as u16), Print(line))?;
+        }
+
+        let help = "↑/↓ move  f filter  enter apply  q quit";
+        queue!(out, cursor::MoveTo(0, rows.saturating_sub(1) as u16), Print(help))?;
+
+        out.flush()
+    }
+
+    // Returns whether the picker is done and what was picked.
+    fn handle(&mut self, key: KeyEvent) -> Option<Option<Picked>> {
+        let code = key.code;
+        // raw mode keeps ctrl-c from interrupting us, so it quits like q
+        if code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL) {
+            return Some(None);
+        }
+
+        let page = terminal::size().map(|s| s.1 as usize).unwrap_or(10).saturating_sub(2).max(1);
+
+        if self.adjusting_filter {
+            match code {
+                KeyCode::Left | KeyCode::Char('h') => self.hue = (self.hue - HUE_STEP + 540) % 360 - 180,
+                KeyCode::Right | KeyCode::Char('l') => self.hue = (self.hue + HUE_STEP + 540) % 360 - 180,
+                KeyCode::Char('0') => self.hue = 0,
+                KeyCode::Char('f') | KeyCode::Esc => self.adjusting_filter = false,
+                KeyCode::Enter => self.adjusting_filter = false,
+                _ => (),
+            }
+            return None;
+        }
+
+        match code {
+            KeyCode::Up | KeyCode::Char('k') => self.select(self.selected.saturating_sub(1)),
+            KeyCode::Down | KeyCode::Char('j') => self.select(self.selected + 1),
+            KeyCode::PageUp => self.select(self.selected.saturating_sub(page)),
+            KeyCode::PageDown => self.select(self.selected + page),
+            KeyCode::Home => self.select(0),
+            KeyCode::End => self.select(self.themes.len()),
+            KeyCode::Char('f') => self.adjusting_filter = true,
+            KeyCode::Enter => {
+                return Some(Some(Picked {
+                    index: self.selected,
+                    hue: self.hue,
+                }))
+            }
+            KeyCode::Esc | KeyCode::Char('q') => return Some(None),
+            _ => (),
+        }
+        None
+    }
+}
+
+fn run(out: &mut impl Write, themes: &[(String, Theme)], space: ColorSpace) -> io::Result<Option<Picked>> {
+    let mut state = State {
+        themes,
+        selected: 0,
+        offset: 0,
+        hue: 0,
+        space,
+        adjusting_filter: false,
+    };
+
+    loop {
+        state.draw(out)?;
+        match event::read()? {
+            Event::Key(key) if key.kind == KeyEventKind::Press => {
+                if let Some(picked) = state.handle(key) {
+                    return Ok(picked);
+                }
+            }
+            _ => (),
+        }
+    }
+}
+
+// Lets the user browse the themes with a preview of their colors. Returns
+// the picked theme and the hue rotation to apply to it in `space`, if any was
+// picked.
+pub fn pick(themes: &[(String, Theme)], space: ColorSpace) -> Result<Option<Picked>, String> {
+    if themes.is_empty() {
+        return Err("no themes to pick from".to_string());
+    }
+
+    let mut out = io::stdout();
+    terminal::enable_raw_mode().map_err(|e| e.to_string())?;
+    execute!(out, terminal::EnterAlternateScreen, cursor::Hide).map_err(|e| e.to_string())?;
+
+    let picked = run(&mut out, themes, space);
+
+    // restore the terminal whatever happened
+    let _ = execute!(out, cursor::Show, terminal::LeaveAlternateScreen);
+    let _ = terminal::disable_raw_mode();
+
+    picked.map_err(|e| e.to_string())
+}
@@ -0,0 +1,192 @@
+extern crate resvg;
+
+use self::resvg::{tiny_skia, usvg};
+use std::fmt::Write;
+
+use ansi::{bar_colors, window_colors, workspace_colors, Colors};
+use colors;
+use theme::Theme;
+
+const WIDTH: u32 = 800;
+const HEIGHT: u32 = 500;
+const TITLE_HEIGHT: u32 = 20;
+const BAR_HEIGHT: u32 = 22;
+const BORDER: u32 = 2;
+
+const WALLPAPER: &str = "#303030";
+const CLIENT: &str = "#1c1c1c";
+const CLIENT_TEXT: &str = "#5c5c5c";
+const FONT: &str = "DejaVu Sans Mono, monospace";
+
+// A color as svg attributes, since not everything reads #rrggbbaa.
+fn paint(attribute: &str, color: &str) -> String {
+    match colors::parse_hex(color) {
+        Ok(c) if c.alpha < 255 => format!(
+            "{}=\"#{:02x}{:02x}{:02x}\" {}-opacity=\"{:.3}\"",
+            attribute,
+            c.red,
+            c.green,
+            c.blue,
+            attribute,
+            c.alpha as f32 / 255.0
+        ),
+        Ok(c) => format!("{}=\"#{:02x}{:02x}{:02x}\"", attribute, c.red, c.green, c.blue),
+        Err(_) => format!("{}=\"none\"", attribute),
+    }
+}
+
+struct Svg {
+    out: String,
+}
+
+impl Svg {
+    fn rect(&mut self, x: u32, y: u32, w: u32, h: u32, fill: &str) {
+        writeln!(
+            self.out,
+            "  <rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" {}/>",
+            x,
+            y,
+            w,
+            h,
+            paint("fill", fill)
+        )
+        .unwrap();
+    }
+
+    fn text(&mut self, x: u32, y: u32, anchor: &str, fill: &str, text: &str) {
+        writeln!(
+            self.out,
+            "  <text x=\"{}\" y=\"{}\" text-anchor=\"{}\" {}>{}</text>",
+            x,
+            y,
+            anchor,
+            paint("fill", fill),
+            text
+        )
+        .unwrap();
+    }
+
+    // A title bar as i3 draws it in the normal border style.
+    fn title(&mut self, x: u32, y: u32, w: u32, colors: &Colors, title: &str) {
+        self.rect(x, y, w, TITLE_HEIGHT, &colors.border);
+        self.rect(x + 1, y + 1, w - 2, TITLE_HEIGHT - 2, &colors.background);
+        self.text(x + 6, y + 14, "start", &colors.text, title);
+    }
+
+    // The client area of a window with its border. The indicator is on the
+    // bottom edge, where the next window would open in a vertical split.
+    fn client(&mut self, x: u32, y: u32, w: u32, h: u32, colors: &Colors, indicator: bool) {
+        self.rect(x, y, w, h, &colors.border);
+        if indicator {
+            self.rect(x, y + h - BORDER, w, BORDER, &colors.indicator);
+        }
+        self.rect(x + BORDER, y, w - 2 * BORDER, h - BORDER, CLIENT);
+        for (i, length) in [0.6, 0.4, 0.7, 0.25].iter().enumerate() {
+            let line = ((w - 4 * BORDER - 16) as f32 * length) as u32;
+            self.rect(x + BORDER + 8, y + 10 + 14 * i as u32, line, 6, CLIENT_TEXT);
+        }
+    }
+}
+
+// A stylized desktop in the colors of the theme: a tabbed container, a
+// stacked container and a plain window over a bar.
+pub fn svg(theme: &Theme) -> String {
+    let mut svg = Svg { out: String::new() };
+    writeln!(
+        svg.out,
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" viewBox=\"0 0 {} {}\" font-family=\"{}\" font-size=\"12\">",
+        WIDTH, HEIGHT, WIDTH, HEIGHT, FONT
+    )
+    .unwrap();
+
+    let windows = window_colors(theme);
+    let state = |name: &str| &windows.iter().find(|w| w.0 == name).unwrap().1;
+    let (focused, focused_inactive) = (state("focused"), state("focused_inactive"));
+    let (unfocused, urgent) = (state("unfocused"), state("urgent"));
+
+    let desktop = HEIGHT - BAR_HEIGHT;
+    let half = WIDTH / 2;
+    svg.rect(0, 0, WIDTH, HEIGHT, WALLPAPER);
+
+    // tabbed container with the focused window
+    let tab = half / 3;
+    svg.title(0, 0, tab, focused, "vim i3-style");
+    svg.title(tab, 0, tab, unfocused, "htop");
+    svg.title(2 * tab, 0, half - 2 * tab, urgent, "irc");
+    svg.client(0, TITLE_HEIGHT, half, desktop - TITLE_HEIGHT, focused, true);
+
+    // stacked container that doesn't have the focus
+    let stack = desktop / 2;
+    svg.title(half, 0, half, focused_inactive, "~/src/i3-style");
+    svg.title(half, TITLE_HEIGHT, half, unfocused, "man i3");
+    svg.client(half, 2 * TITLE_HEIGHT, half, stack - 2 * TITLE_HEIGHT, focused_inactive, false);
+
+    svg.title(half, stack, half, unfocused, "firefox");
+    svg.client(half, stack + TITLE_HEIGHT, half, desktop - stack - TITLE_HEIGHT, unfocused, false);
+
+    // the bar
+    let [background, statusline, separator] = bar_colors(theme);
+    svg.rect(0, desktop, WIDTH, BAR_HEIGHT, &background);
+    let mut x = 2;
+    for (i, (_, colors)) in workspace_colors(theme).iter().enumerate() {
+        svg.rect(x, desktop + 2, 26, BAR_HEIGHT - 4, &colors.border);
+        svg.rect(x + 1, desktop + 3, 24, BAR_HEIGHT - 6, &colors.background);
+        svg.text(x + 13, desktop + 15, "middle", &colors.text, &(i + 1).to_string());
+        x += 28;
+    }
+
+    let mut x = WIDTH - 8;
+    let status = ["12:00", "100%", "W: 192.168.1.2"];
+    for (i, s) in status.iter().enumerate() {
+        if i > 0 {
+            svg.rect(x, desktop + 4, 1, BAR_HEIGHT - 8, &separator);
+            x -= 8;
+        }
+        svg.text(x, desktop + 15, "end", &statusline, s);
+        x -= 7 * s.len() as u32 + 8;
+    }
+
+    svg.out.push_str("</svg>\n");
+    svg.out
+}
+
+pub fn png(theme: &Theme) -> Result<Vec<u8>, String> {
+    let mut options = usvg::Options::default();
+    options.fontdb_mut().load_system_fonts();
+    let tree = usvg::Tree::from_str(&svg(theme), &options).map_err(|e| e.to_string())?;
+
+    let mut pixmap = tiny_skia::Pixmap::new(WIDTH, HEIGHT).unwrap();
+    resvg::render(&tree, tiny_skia::Transform::default(), &mut pixmap.as_mut());
+    pixmap.encode_png().map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use theme::from_yaml;
+    use yaml_rust::YamlLoader;
+
+    #[test]
+    fn test_render() {
+        let docs = YamlLoader::load_from_str(
+            "window_colors:
+  focused:
+    border: '#111111'
+    background: '#22222280'
+    text: '#333333'
+    indicator: '#444444'
+",
+        )
+        .unwrap();
+        let theme = from_yaml(&docs[0]).unwrap();
+
+        let svg = svg(&theme);
+        assert!(svg.contains("<rect x=\"0\" y=\"20\" width=\"400\" height=\"458\" fill=\"#111111\"/>"));
+        assert!(svg.contains("fill=\"#222222\" fill-opacity=\"0.502\""));
+        assert!(svg.contains("<rect x=\"0\" y=\"476\" width=\"400\" height=\"2\" fill=\"#444444\"/>"));
+
+        let png = png(&theme).unwrap();
+        assert_eq!(&png[..8], b"\x89PNG\r\n\x1a\n");
+    }
+}
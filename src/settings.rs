@@ -0,0 +1,227 @@
+extern crate serde;
+extern crate toml;
+
+use self::serde::Deserialize;
+use filters::ColorSpace;
+use std::env;
+use std::ffi::OsString;
+use std::fs;
+use std::path::PathBuf;
+
+// The defaults from `config.toml`. Everything is optional so that a team
+// default can be overridden key by key.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Settings {
+    pub config: Option<PathBuf>,
+    pub in_place: Option<bool>,
+    pub reload: Option<bool>,
+    pub filter: Option<f32>,
+    pub color_space: Option<ColorSpace>,
+    pub favorites: Option<Vec<String>>,
+    pub backups: Option<usize>,
+}
+
+impl Settings {
+    // Takes every key that is set in `other`.
+    pub fn merge(&mut self, other: Settings) {
+        if other.config.is_some() {
+            self.config = other.config;
+        }
+        if other.in_place.is_some() {
+            self.in_place = other.in_place;
+        }
+        if other.reload.is_some() {
+            self.reload = other.reload;
+        }
+        if other.filter.is_some() {
+            self.filter = other.filter;
+        }
+        if other.color_space.is_some() {
+            self.color_space = other.color_space;
+        }
+        if other.favorites.is_some() {
+            self.favorites = other.favorites;
+        }
+        if other.backups.is_some() {
+            self.backups = other.backups;
+        }
+    }
+
+    pub fn in_place(&self) -> bool {
+        self.in_place.unwrap_or(false)
+    }
+
+    pub fn reload(&self) -> bool {
+        self.reload.unwrap_or(false)
+    }
+
+    pub fn color_space(&self) -> ColorSpace {
+        self.color_space.unwrap_or_default()
+    }
+
+    pub fn favorites(&self) -> &[String] {
+        self.favorites.as_ref().map_or(&[], Vec::as_slice)
+    }
+}
+
+fn expand_home(path: &str, home: Option<&OsString>) -> PathBuf {
+    match (path.strip_prefix("~/"), home) {
+        (Some(rest), Some(home)) => PathBuf::from(home).join(rest),
+        _ => PathBuf::from(path),
+    }
+}
+
+fn parse_with_home(contents: &str, home: Option<&OsString>) -> Result<Settings, String> {
+    let mut settings: Settings = toml::from_str(contents).map_err(|e| e.to_string())?;
+    if let Some(config) = settings.config.take() {
+        settings.config = Some(expand_home(&config.to_string_lossy(), home));
+    }
+    Ok(settings)
+}
+
+pub fn parse(contents: &str) -> Result<Settings, String> {
+    parse_with_home(contents, env::var_os("HOME").as_ref())
+}
+
+// `$XDG_CONFIG_HOME`, or `~/.config` when it isn't set.
+pub(crate) fn config_home(config_home: Option<OsString>, home: Option<&OsString>) -> Option<PathBuf> {
+    match config_home.map(PathBuf::from) {
+        Some(d) if d.is_absolute() => Some(d),
+        _ => home.map(|h| PathBuf::from(h).join(".config")),
+    }
+}
+
+// `$XDG_CONFIG_DIRS`, or `/etc/xdg` when it isn't set.
+pub(crate) fn config_dirs(config_dirs: Option<OsString>) -> Vec<PathBuf> {
+    let dirs: Vec<PathBuf> = match config_dirs {
+        Some(dirs) => env::split_paths(&dirs).filter(|p| p.is_absolute()).collect(),
+        None => Vec::new(),
+    };
+    if dirs.is_empty() {
+        vec![PathBuf::from("/etc/xdg")]
+    } else {
+        dirs
+    }
+}
+
+fn settings_files_from(
+    xdg_config_home: Option<OsString>,
+    xdg_config_dirs: Option<OsString>,
+    home: Option<OsString>,
+) -> Vec<PathBuf> {
+    // the most important XDG directory comes first, so it is read last
+    let mut files: Vec<PathBuf> = config_dirs(xdg_config_dirs)
+        .into_iter()
+        .rev()
+        .map(|d| d.join("i3-style/config.toml"))
+        .collect();
+    if let Some(dir) = config_home(xdg_config_home, home.as_ref()) {
+        files.push(dir.join("i3-style/config.toml"));
+    }
+    files
+}
+
+// The settings files in the order they are read, which is the team defaults
+// under `$XDG_CONFIG_DIRS` first and the user's own file last.
+pub fn settings_files() -> Vec<PathBuf> {
+    settings_files_from(
+        env::var_os("XDG_CONFIG_HOME"),
+        env::var_os("XDG_CONFIG_DIRS"),
+        env::var_os("HOME"),
+    )
+}
+
+pub fn load_files(files: &[PathBuf]) -> Result<Settings, String> {
+    let mut settings = Settings::default();
+    for file in files.iter().filter(|f| f.is_file()) {
+        let contents = fs::read_to_string(file).map_err(|e| format!("{}: {}", file.display(), e))?;
+        settings.merge(parse(&contents).map_err(|e| format!("{}: {}", file.display(), e))?);
+    }
+    Ok(settings)
+}
+
+pub fn load() -> Result<Settings, String> {
+    load_files(&settings_files())
+}
+
+fn i3_configs_from(
+    xdg_config_home: Option<OsString>,
+    xdg_config_dirs: Option<OsString>,
+    home: Option<OsString>,
+) -> Vec<PathBuf> {
+    let mut configs = Vec::new();
+    if let Some(dir) = config_home(xdg_config_home, home.as_ref()) {
+        configs.push(dir.join("i3/config"));
+    }
+    for dir in config_dirs(xdg_config_dirs) {
+        configs.push(dir.join("i3/config"));
+    }
+    if let Some(home) = home {
+        configs.push(PathBuf::from(home).join(".i3/config"));
+    }
+    configs.push(PathBuf::from("/etc/i3/config"));
+    configs
+}
+
+// The i3 config to use when none is given, which is the `config` setting or
+// else the first one in the order i3 itself looks for its config.
+pub fn i3_config(settings: &Settings) -> Option<PathBuf> {
+    if let Some(ref config) = settings.config {
+        return Some(config.clone());
+    }
+    i3_configs_from(
+        env::var_os("XDG_CONFIG_HOME"),
+        env::var_os("XDG_CONFIG_DIRS"),
+        env::var_os("HOME"),
+    )
+    .into_iter()
+    .find(|p| p.exists())
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate tempfile;
+
+    use super::*;
+    use self::tempfile::tempdir;
+
+    #[test]
+    fn test_settings() {
+        let home = OsString::from("/home/me");
+        let files = settings_files_from(None, Some(OsString::from("/team:/etc/xdg")), Some(home.clone()));
+        assert_eq!(
+            files,
+            vec![
+                PathBuf::from("/etc/xdg/i3-style/config.toml"),
+                PathBuf::from("/team/i3-style/config.toml"),
+                PathBuf::from("/home/me/.config/i3-style/config.toml"),
+            ]
+        );
+        assert_eq!(
+            i3_configs_from(None, None, Some(home.clone()))[..2],
+            [PathBuf::from("/home/me/.config/i3/config"), PathBuf::from("/etc/xdg/i3/config")]
+        );
+
+        let dir = tempdir().unwrap();
+        let team = dir.path().join("team.toml");
+        let user = dir.path().join("user.toml");
+        fs::write(&team, "config = \"/etc/i3/config\"\nin_place = true\nfilter = 30\nfavorites = [\"solarized\"]\nbackups = 5\n").unwrap();
+        fs::write(&user, "in_place = false\ncolor_space = \"oklch\"\nbackups = 0\n").unwrap();
+
+        let settings = load_files(&[team, user.clone(), dir.path().join("missing.toml")]).unwrap();
+        assert_eq!(settings.config, Some(PathBuf::from("/etc/i3/config")));
+        assert!(!settings.in_place());
+        assert_eq!(settings.filter, Some(30.0));
+        assert_eq!(settings.color_space(), ColorSpace::Oklch);
+        assert_eq!(settings.favorites(), ["solarized"]);
+        assert_eq!(settings.backups, Some(0));
+
+        let parsed = parse_with_home("config = \"~/i3.conf\"", Some(&home)).unwrap();
+        assert_eq!(parsed.config, Some(PathBuf::from("/home/me/i3.conf")));
+        assert!(parse("backups = -1").is_err());
+        assert!(parse("color_space = \"rgb\"").is_err());
+        let err = parse("in_palce = true").unwrap_err();
+        assert!(err.starts_with("unknown field `in_palce`"), "{}", err);
+    }
+}
@@ -0,0 +1,355 @@
+use std::env;
+use std::ffi::OsString;
+use std::path::PathBuf;
+
+use settings::{config_dirs, config_home};
+use theme::StatusColors;
+
+pub const FORMATS: &[&str] = &["i3status", "i3status-rust", "i3blocks"];
+
+// The configs a status command looks for, in the order it looks for them.
+fn default_configs_from(
+    format: &str,
+    xdg_config_home: Option<OsString>,
+    xdg_config_dirs: Option<OsString>,
+    home: Option<OsString>,
+) -> Vec<PathBuf> {
+    let user = config_home(xdg_config_home, home.as_ref());
+    let home = home.map(PathBuf::from);
+    let system = config_dirs(xdg_config_dirs);
+    let mut configs = Vec::new();
+    match format {
+        "i3status" => {
+            configs.extend(user.map(|d| d.join("i3status/config")));
+            configs.extend(system.iter().map(|d| d.join("i3status/config")));
+            configs.extend(home.map(|h| h.join(".i3status.conf")));
+            configs.push(PathBuf::from("/etc/i3status.conf"));
+        }
+        "i3status-rust" => {
+            configs.extend(user.map(|d| d.join("i3status-rust/config.toml")));
+        }
+        "i3blocks" => {
+            configs.extend(user.map(|d| d.join("i3blocks/config")));
+            configs.extend(home.map(|h| h.join(".i3blocks.conf")));
+            configs.extend(system.iter().map(|d| d.join("i3blocks/config")));
+            configs.push(PathBuf::from("/etc/i3blocks.conf"));
+        }
+        _ => (),
+    }
+    configs
+}
+
+// The config a status command reads when it isn't given one, or where the
+// user's own one would go if there is none yet.
+pub fn default_config(format: &str) -> Option<PathBuf> {
+    let configs = default_configs_from(
+        format,
+        env::var_os("XDG_CONFIG_HOME"),
+        env::var_os("XDG_CONFIG_DIRS"),
+        env::var_os("HOME"),
+    );
+    match configs.iter().find(|c| c.exists()) {
+        Some(config) => Some(config.clone()),
+        None => configs.into_iter().next(),
+    }
+}
+
+fn leading_spaces(line: &str) -> &str {
+    &line[..line.len() - line.trim_start().len()]
+}
+
+// The key of a `key = value` line, which all three formats have.
+fn key_of(line: &str) -> Option<&str> {
+    let line = line.trim();
+    if line.starts_with('#') || line.starts_with('[') {
+        return None;
+    }
+    let mut parts = line.splitn(2, '=');
+    let key = parts.next()?.trim();
+    parts.next()?;
+    Some(key)
+}
+
+fn value_of(line: &str) -> Option<&str> {
+    let mut parts = line.splitn(2, '=');
+    parts.next();
+    parts.next().map(|v| v.trim().trim_matches('"'))
+}
+
+// Sets the keys of the section in `lines[start..end]`. The ones it has are
+// changed where they are and the others are added after its last line, so
+// everything else in the file stays as it was.
+fn set_keys(
+    lines: &mut Vec<String>,
+    start: usize,
+    end: usize,
+    values: &[(&str, &String)],
+    line_for: &dyn Fn(&str, &str, &str) -> String,
+    indent: &str,
+) {
+    let mut missing = Vec::new();
+    for (key, value) in values {
+        match (start..end).find(|&i| key_of(&lines[i]) == Some(*key)) {
+            Some(i) => {
+                let line = line_for(leading_spaces(&lines[i]), key, value);
+                lines[i] = line;
+            }
+            None => missing.push((key, value)),
+        }
+    }
+
+    let indent = (start..end)
+        .find(|&i| key_of(&lines[i]).is_some())
+        .map(|i| leading_spaces(&lines[i]).to_string())
+        .unwrap_or_else(|| indent.to_string());
+    let at = (start..end)
+        .rev()
+        .find(|&i| !lines[i].trim().is_empty())
+        .map(|i| i + 1)
+        .unwrap_or(start);
+    for (i, (key, value)) in missing.into_iter().enumerate() {
+        lines.insert(at + i, line_for(&indent, key, value));
+    }
+}
+
+fn present<'a>(values: &[(&'a str, &'a Option<String>)]) -> Vec<(&'a str, &'a String)> {
+    values
+        .iter()
+        .filter_map(|(key, value)| value.as_ref().map(|v| (*key, v)))
+        .collect()
+}
+
+// The colors go in the `general` block, which is added if there isn't one.
+fn i3status(lines: &mut Vec<String>, colors: &StatusColors) {
+    let values = present(&[
+        ("color_good", &colors.good),
+        ("color_degraded", &colors.degraded),
+        ("color_bad", &colors.bad),
+        ("color_separator", &colors.separator),
+    ]);
+    let line_for = |indent: &str, key: &str, value: &str| format!("{}{} = \"{}\"", indent, key, value);
+
+    let start = lines.iter().position(|l| {
+        let l = l.trim();
+        l.starts_with("general") && l.ends_with('{') && l[7..l.len() - 1].trim().is_empty()
+    });
+    let start = match start {
+        Some(start) => start,
+        None => {
+            lines.splice(0..0, vec!["general {".to_string(), "}".to_string(), String::new()]);
+            0
+        }
+    };
+    let end = (start + 1..lines.len())
+        .find(|&i| lines[i].trim() == "}")
+        .unwrap_or(lines.len());
+    set_keys(lines, start + 1, end, &values, &line_for, "        ");
+}
+
+// The colors go in `[theme.overrides]`, which is added at the end if there
+// isn't one.
+fn i3status_rust(lines: &mut Vec<String>, colors: &StatusColors) {
+    let values = present(&[
+        ("good_fg", &colors.good),
+        ("warning_fg", &colors.degraded),
+        ("critical_fg", &colors.bad),
+        ("separator_fg", &colors.separator),
+    ]);
+    let line_for = |indent: &str, key: &str, value: &str| format!("{}{} = \"{}\"", indent, key, value);
+
+    let header = |l: &String| l.split('#').next().unwrap().replace(' ', "") == "[theme.overrides]";
+    let start = match lines.iter().position(header) {
+        Some(start) => start,
+        None => {
+            if lines.last().is_some_and(|l| !l.trim().is_empty()) {
+                lines.push(String::new());
+            }
+            lines.push("[theme.overrides]".to_string());
+            lines.len() - 1
+        }
+    };
+    let end = (start + 1..lines.len())
+        .find(|&i| lines[i].trim_start().starts_with('['))
+        .unwrap_or(lines.len());
+    set_keys(lines, start + 1, end, &values, &line_for, "");
+}
+
+// i3blocks passes the properties of the global section to every block
+// script, so the colors are set there. Blocks that use one of the colors
+// for their `color` are changed along with it.
+fn i3blocks(lines: &mut Vec<String>, colors: &StatusColors) {
+    let values = present(&[
+        ("color_good", &colors.good),
+        ("color_degraded", &colors.degraded),
+        ("color_bad", &colors.bad),
+    ]);
+    let line_for = |indent: &str, key: &str, value: &str| format!("{}{}={}", indent, key, value);
+
+    let end = lines
+        .iter()
+        .position(|l| l.trim_start().starts_with('['))
+        .unwrap_or(lines.len());
+
+    let mut replaced = Vec::new();
+    for (key, value) in &values {
+        let old = (0..end).find(|&i| key_of(&lines[i]) == Some(*key));
+        if let Some(old) = old.and_then(|i| value_of(&lines[i])) {
+            replaced.push((old.to_lowercase(), value.to_string()));
+        }
+    }
+    for line in lines[end..].iter_mut() {
+        if key_of(line) != Some("color") {
+            continue;
+        }
+        let old = value_of(line).unwrap_or("").to_lowercase();
+        if let Some((_, value)) = replaced.iter().find(|r| r.0 == old) {
+            *line = line_for(leading_spaces(line), "color", value);
+        }
+    }
+
+    set_keys(lines, 0, end, &values, &line_for, "");
+    // keep the global section apart from the first block
+    let first_block = lines
+        .iter()
+        .position(|l| l.trim_start().starts_with('['));
+    if let Some(i) = first_block {
+        if i > 0 && !lines[i - 1].trim().is_empty() {
+            lines.insert(i, String::new());
+        }
+    }
+}
+
+// Sets the status colors in the config of the status command.
+pub fn write(format: &str, config: &str, colors: &StatusColors) -> Result<String, String> {
+    let mut lines: Vec<String> = config.lines().map(|l| l.to_string()).collect();
+    match format {
+        "i3status" => i3status(&mut lines, colors),
+        "i3status-rust" => i3status_rust(&mut lines, colors),
+        "i3blocks" => i3blocks(&mut lines, colors),
+        _ => return Err(format!("unknown status command: {}", format)),
+    }
+    Ok(lines.join("\n") + "\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn colors() -> StatusColors {
+        StatusColors {
+            good: Some("#859900".to_string()),
+            degraded: Some("#b58900".to_string()),
+            bad: Some("#dc322f".to_string()),
+            separator: None,
+        }
+    }
+
+    #[test]
+    fn test_default_configs() {
+        let home = Some(OsString::from("/home/me"));
+        let configs = default_configs_from("i3status", Some(OsString::from("/xdg")), None, home.clone());
+        assert_eq!(
+            configs,
+            vec![
+                PathBuf::from("/xdg/i3status/config"),
+                PathBuf::from("/etc/xdg/i3status/config"),
+                PathBuf::from("/home/me/.i3status.conf"),
+                PathBuf::from("/etc/i3status.conf"),
+            ]
+        );
+        assert_eq!(
+            default_configs_from("i3status-rust", None, None, home)[0],
+            PathBuf::from("/home/me/.config/i3status-rust/config.toml")
+        );
+    }
+
+    #[test]
+    fn test_status() {
+        let i3status = "# i3status configuration file.
+general {
+        colors = true
+        color_bad = \"#FF0000\"
+        interval = 5
+}
+
+order += \"disk /\"
+";
+        assert_eq!(
+            write("i3status", i3status, &colors()).unwrap(),
+            "# i3status configuration file.
+general {
+        colors = true
+        color_bad = \"#dc322f\"
+        interval = 5
+        color_good = \"#859900\"
+        color_degraded = \"#b58900\"
+}
+
+order += \"disk /\"
+"
+        );
+        assert_eq!(
+            write("i3status", "order += \"load\"\n", &colors()).unwrap(),
+            "general {
+        color_good = \"#859900\"
+        color_degraded = \"#b58900\"
+        color_bad = \"#dc322f\"
+}
+
+order += \"load\"
+"
+        );
+
+        let i3status_rust = "[theme]
+theme = \"plain\"
+
+[theme.overrides]
+idle_bg = \"#000000\"
+good_fg = \"#00ff00\"
+
+[[block]]
+block = \"cpu\"
+";
+        assert_eq!(
+            write("i3status-rust", i3status_rust, &colors()).unwrap(),
+            "[theme]
+theme = \"plain\"
+
+[theme.overrides]
+idle_bg = \"#000000\"
+good_fg = \"#859900\"
+warning_fg = \"#b58900\"
+critical_fg = \"#dc322f\"
+
+[[block]]
+block = \"cpu\"
+"
+        );
+
+        let i3blocks = "command=/usr/share/i3blocks/$BLOCK_NAME
+color_good=#00FF00
+
+[battery]
+color=#00ff00
+interval=30
+
+[time]
+color=#ffffff
+";
+        assert_eq!(
+            write("i3blocks", i3blocks, &colors()).unwrap(),
+            "command=/usr/share/i3blocks/$BLOCK_NAME
+color_good=#859900
+color_degraded=#b58900
+color_bad=#dc322f
+
+[battery]
+color=#859900
+interval=30
+
+[time]
+color=#ffffff
+"
+        );
+    }
+}
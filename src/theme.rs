@@ -1,20 +1,32 @@
 extern crate colornamer;
 extern crate linked_hash_map;
 extern crate regex;
+extern crate serde;
 extern crate yaml_rust;
 
+use self::serde::{Deserialize, Deserializer, Serialize};
 use linked_hash_map::LinkedHashMap;
+use std::convert::TryFrom;
 use std::fs::File;
 use std::io::prelude::*;
-use std::io::BufReader;
+use std::io::{self, BufReader};
+use std::path::Path;
 use yaml_rust::Yaml;
+use colors;
+use colors::Palette;
+use format;
 use filters::{Filter, Filterable};
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
 pub struct ColorGroup {
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub border: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub background: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub text: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub indicator: Option<String>,
 }
 
@@ -38,6 +50,13 @@ impl ColorGroup {
         }
     }
 
+    fn merge(&mut self, overlay: ColorGroup) {
+        merge_color(&mut self.border, overlay.border);
+        merge_color(&mut self.background, overlay.background);
+        merge_color(&mut self.text, overlay.text);
+        merge_color(&mut self.indicator, overlay.indicator);
+    }
+
     fn empty() -> ColorGroup {
         ColorGroup {
             border: None,
@@ -47,41 +66,32 @@ impl ColorGroup {
         }
     }
 
-    fn to_yaml(&self, color_map: &ColorMap) -> Yaml {
-        let mut group_yaml: LinkedHashMap<Yaml, Yaml> = LinkedHashMap::new();
-        if self.border.is_some() {
-            group_yaml.insert(
-                Yaml::String("border".to_string()),
-                Yaml::String(color_map.get_color(&self.border.as_ref().unwrap().to_string())),
-            );
-        }
-        if self.background.is_some() {
-            group_yaml.insert(
-                Yaml::String("background".to_string()),
-                Yaml::String(color_map.get_color(&self.background.as_ref().unwrap().to_string())),
-            );
-        }
-        if self.text.is_some() {
-            group_yaml.insert(
-                Yaml::String("text".to_string()),
-                Yaml::String(color_map.get_color(&self.text.as_ref().unwrap().to_string())),
-            );
-        }
-        if self.indicator.is_some() {
-            group_yaml.insert(
-                Yaml::String("indicator".to_string()),
-                Yaml::String(color_map.get_color(&self.indicator.as_ref().unwrap().to_string())),
-            );
-        }
-        Yaml::Hash(group_yaml)
+}
+
+fn merge_color(base: &mut Option<String>, overlay: Option<String>) {
+    if overlay.is_some() {
+        *base = overlay;
     }
 }
 
-#[derive(Debug)]
+fn merge_group(base: &mut Option<ColorGroup>, overlay: Option<ColorGroup>) {
+    match (base.as_mut(), overlay) {
+        (Some(group), Some(overlay)) => group.merge(overlay),
+        (None, Some(overlay)) => *base = Some(overlay),
+        (_, None) => (),
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
 pub struct WindowColors {
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub focused: Option<ColorGroup>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub focused_inactive: Option<ColorGroup>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub unfocused: Option<ColorGroup>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub urgent: Option<ColorGroup>,
 }
 
@@ -94,14 +104,22 @@ impl Filterable for WindowColors {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
 pub struct BarColors {
-    pub separator: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub background: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub statusline: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub separator: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub focused_workspace: Option<ColorGroup>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub active_workspace: Option<ColorGroup>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub inactive_workspace: Option<ColorGroup>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub urgent_workspace: Option<ColorGroup>,
 }
 
@@ -117,17 +135,315 @@ impl Filterable for BarColors {
     }
 }
 
-#[derive(Debug)]
-pub struct Theme {
+// The colors of the status line itself, which i3status and the like use for
+// the state of their blocks.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct StatusColors {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub good: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub degraded: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bad: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub separator: Option<String>,
+}
+
+impl StatusColors {
+    fn empty() -> StatusColors {
+        StatusColors {
+            good: None,
+            degraded: None,
+            bad: None,
+            separator: None,
+        }
+    }
+}
+
+impl Filterable for StatusColors {
+    fn apply_filter(&mut self, filter: &impl Filter) {
+        self.good.apply_filter(filter);
+        self.degraded.apply_filter(filter);
+        self.bad.apply_filter(filter);
+        self.separator.apply_filter(filter);
+    }
+}
+
+// The fonts of window titles and of the bar, as i3 takes them, e.g.
+// `pango:DejaVu Sans Mono 10`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(from = "FontSpec")]
+pub struct Font {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub window: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bar: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Borders {
+    #[serde(skip_serializing_if = "Option::is_none", deserialize_with = "border_style")]
+    pub default_border: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none", deserialize_with = "border_style")]
+    pub default_floating_border: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hide_edge_borders: Option<String>,
+}
+
+// Gaps are supported by i3 since 4.22 and by sway.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Gaps {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub inner: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub outer: Option<i64>,
+}
+
+// A single font is the font of everything, as with `font` in the config.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum FontSpec {
+    All(String),
+    Each(FontParts),
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct FontParts {
+    #[serde(default)]
+    window: Option<String>,
+    #[serde(default)]
+    bar: Option<String>,
+}
+
+impl From<FontSpec> for Font {
+    fn from(spec: FontSpec) -> Font {
+        match spec {
+            FontSpec::All(font) => Font { window: Some(font), bar: None },
+            FontSpec::Each(parts) => Font { window: parts.window, bar: parts.bar },
+        }
+    }
+}
+
+// Versions and the like are easily written as bare numbers.
+fn meta_string<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<String>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum MetaString {
+        Text(String),
+        Integer(i64),
+        Real(f64),
+    }
+    Ok(Option::<MetaString>::deserialize(deserializer)?.map(|s| match s {
+        MetaString::Text(text) => text,
+        MetaString::Integer(i) => i.to_string(),
+        MetaString::Real(r) => r.to_string(),
+    }))
+}
+
+// A bare width is taken as a pixel border.
+fn border_style<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<String>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Border {
+        Width(u32),
+        Style(String),
+    }
+    Ok(match Border::deserialize(deserializer)? {
+        Border::Width(width) => Some(format!("pixel {}", width)),
+        Border::Style(style) => Some(style),
+    })
+}
+
+// The names of the colors in a theme.
+pub const WINDOW_GROUPS: &[&str] = &["focused", "focused_inactive", "unfocused", "urgent"];
+pub const BAR_COLORS: &[&str] = &["separator", "background", "statusline"];
+pub const BAR_GROUPS: &[&str] = &[
+    "focused_workspace",
+    "active_workspace",
+    "inactive_workspace",
+    "urgent_workspace",
+];
+pub const PARTS: &[&str] = &["border", "background", "text", "indicator"];
+
+const BORDER_STYLES: &[&str] = &["normal", "none", "pixel"];
+const HIDE_EDGE_BORDERS: &[&str] = &["none", "vertical", "horizontal", "both", "smart", "smart_no_gaps"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Variant {
+    Dark,
+    Light,
+}
+
+impl Variant {
+    pub fn from_name(variant: &str) -> Option<Variant> {
+        match variant {
+            "dark" => Some(Variant::Dark),
+            "light" => Some(Variant::Light),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match *self {
+            Variant::Dark => "dark",
+            Variant::Light => "light",
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Meta {
+    #[serde(skip_serializing_if = "Option::is_none", deserialize_with = "meta_string")]
     pub description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none", deserialize_with = "meta_string")]
+    pub author: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none", deserialize_with = "meta_string")]
+    pub license: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none", deserialize_with = "meta_string")]
+    pub version: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub variant: Option<Variant>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none", deserialize_with = "meta_string")]
+    pub homepage: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(try_from = "ThemeFile", into = "ThemeFile")]
+pub struct Theme {
+    pub meta: Meta,
     pub window_colors: Option<WindowColors>,
     pub bar_colors: Option<BarColors>,
+    pub status_colors: Option<StatusColors>,
+    pub font: Option<Font>,
+    pub borders: Option<Borders>,
+    pub gaps: Option<Gaps>,
 }
 
 impl Filterable for Theme {
     fn apply_filter(&mut self, filter: &impl Filter) {
         self.window_colors.apply_filter(filter);
         self.bar_colors.apply_filter(filter);
+        self.status_colors.apply_filter(filter);
+    }
+}
+
+// A theme as it is written in a file, where colors can be names from `colors`
+// or expressions over them. `Theme` is read and written through this, so a
+// written theme names its colors.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ThemeFile {
+    pub meta: Meta,
+    #[serde(skip_serializing_if = "LinkedHashMap::is_empty")]
+    pub colors: LinkedHashMap<String, String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub window_colors: Option<WindowColors>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bar_colors: Option<BarColors>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status_colors: Option<StatusColors>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub font: Option<Font>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub borders: Option<Borders>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub gaps: Option<Gaps>,
+}
+
+// Resolves the names and expressions of the file and checks what serde
+// can't.
+impl TryFrom<ThemeFile> for Theme {
+    type Error = String;
+
+    fn try_from(mut file: ThemeFile) -> Result<Theme, String> {
+        let mut palette = Palette::new(&file.colors)?;
+
+        if let Some(ref mut wc) = file.window_colors {
+            resolve_group(&mut palette, "window_colors.focused", &mut wc.focused)?;
+            resolve_group(&mut palette, "window_colors.focused_inactive", &mut wc.focused_inactive)?;
+            resolve_group(&mut palette, "window_colors.unfocused", &mut wc.unfocused)?;
+            resolve_group(&mut palette, "window_colors.urgent", &mut wc.urgent)?;
+        }
+        if let Some(ref mut bc) = file.bar_colors {
+            resolve_color(&mut palette, "bar_colors.background", &mut bc.background)?;
+            resolve_color(&mut palette, "bar_colors.statusline", &mut bc.statusline)?;
+            resolve_color(&mut palette, "bar_colors.separator", &mut bc.separator)?;
+            resolve_group(&mut palette, "bar_colors.focused_workspace", &mut bc.focused_workspace)?;
+            resolve_group(&mut palette, "bar_colors.active_workspace", &mut bc.active_workspace)?;
+            resolve_group(&mut palette, "bar_colors.inactive_workspace", &mut bc.inactive_workspace)?;
+            resolve_group(&mut palette, "bar_colors.urgent_workspace", &mut bc.urgent_workspace)?;
+        }
+        if let Some(ref mut sc) = file.status_colors {
+            resolve_color(&mut palette, "status_colors.good", &mut sc.good)?;
+            resolve_color(&mut palette, "status_colors.degraded", &mut sc.degraded)?;
+            resolve_color(&mut palette, "status_colors.bad", &mut sc.bad)?;
+            resolve_color(&mut palette, "status_colors.separator", &mut sc.separator)?;
+        }
+        if let Some(ref borders) = file.borders {
+            check_borders(borders)?;
+        }
+
+        Ok(Theme {
+            meta: file.meta,
+            window_colors: file.window_colors,
+            bar_colors: file.bar_colors,
+            status_colors: file.status_colors,
+            font: file.font,
+            borders: file.borders,
+            gaps: file.gaps,
+        })
+    }
+}
+
+impl From<Theme> for ThemeFile {
+    fn from(theme: Theme) -> ThemeFile {
+        let colormap = theme.color_map();
+        let name = |color: &Option<String>| color.as_ref().map(|c| colormap.get_color(c));
+        let group = |group: &Option<ColorGroup>| {
+            group.as_ref().map(|g| ColorGroup {
+                border: name(&g.border),
+                background: name(&g.background),
+                text: name(&g.text),
+                indicator: name(&g.indicator),
+            })
+        };
+
+        ThemeFile {
+            window_colors: theme.window_colors.as_ref().map(|wc| WindowColors {
+                focused: group(&wc.focused),
+                focused_inactive: group(&wc.focused_inactive),
+                unfocused: group(&wc.unfocused),
+                urgent: group(&wc.urgent),
+            }),
+            bar_colors: theme.bar_colors.as_ref().map(|bc| BarColors {
+                separator: name(&bc.separator),
+                background: name(&bc.background),
+                statusline: name(&bc.statusline),
+                focused_workspace: group(&bc.focused_workspace),
+                active_workspace: group(&bc.active_workspace),
+                inactive_workspace: group(&bc.inactive_workspace),
+                urgent_workspace: group(&bc.urgent_workspace),
+            }),
+            status_colors: theme.status_colors.as_ref().map(|sc| StatusColors {
+                good: name(&sc.good),
+                degraded: name(&sc.degraded),
+                bad: name(&sc.bad),
+                separator: name(&sc.separator),
+            }),
+            colors: colormap.colors.clone(),
+            meta: theme.meta,
+            font: theme.font,
+            borders: theme.borders,
+            gaps: theme.gaps,
+        }
     }
 }
 
@@ -143,23 +459,18 @@ impl ColorMap {
         }
     }
 
-    fn has_color(&self, hex: &String) -> bool {
-        for (_key, value) in &self.colors {
-            if hex == value {
-                return true;
-            }
-        }
-        return false;
+    fn has_color(&self, hex: &str) -> bool {
+        self.colors.values().any(|value| hex == value)
     }
 
-    fn get_color(&self, hex: &String) -> String {
+    fn get_color(&self, hex: &str) -> String {
         let hex_uc = hex.to_uppercase();
         for (key, value) in &self.colors {
             if &hex_uc == value {
                 return key.to_string();
             }
         }
-        return hex.to_string();
+        hex.to_string()
     }
 
     fn add_hex(&mut self, hex: &Option<String>) {
@@ -167,49 +478,172 @@ impl ColorMap {
             static ref RE: regex::Regex = regex::Regex::new(r"-(\d+)$").unwrap();
         }
 
-        match hex {
-            &Some(ref h) => {
-                let h = h.to_uppercase();
-                if self.has_color(&h) {
-                    return;
-                }
+        if let Some(h) = hex {
+            let h = h.to_uppercase();
+            if self.has_color(&h) {
+                return;
+            }
 
-                let mut color_name =
-                    colornamer::name_color_hex(h.as_str(), colornamer::Colors::HTML);
-                let ref mut colors = self.colors;
-                while colors.contains_key(&color_name) {
-                    if !RE.is_match(&color_name) {
-                        color_name = color_name + "-1";
-                    } else {
-                        let cpy = color_name.clone();
-                        let captures = RE.captures(cpy.as_str()).unwrap().get(1).unwrap();
-                        let num: String = color_name.chars().skip(captures.start()).collect();
-                        let num: u32 = num.parse().unwrap();
-                        let num = num + 1;
-                        color_name = color_name.chars().take(captures.start()).collect();
-                        color_name = color_name + num.to_string().as_str();
-                    }
+            let mut color_name = colornamer::name_color_hex(h.as_str(), colornamer::Colors::HTML);
+            let colors = &mut self.colors;
+            while colors.contains_key(&color_name) {
+                if !RE.is_match(&color_name) {
+                    color_name += "-1";
+                } else {
+                    let cpy = color_name.clone();
+                    let captures = RE.captures(cpy.as_str()).unwrap().get(1).unwrap();
+                    let num: String = color_name.chars().skip(captures.start()).collect();
+                    let num: u32 = num.parse().unwrap();
+                    let num = num + 1;
+                    color_name = color_name.chars().take(captures.start()).collect();
+                    color_name += num.to_string().as_str();
                 }
-                colors.insert(color_name.to_string(), h.to_string());
             }
-            &None => (),
+            colors.insert(color_name.to_string(), h.to_string());
         }
     }
 
     fn add_color_group(&mut self, group: &Option<ColorGroup>) {
-        match group {
-            &Some(ref g) => {
-                self.add_hex(&g.border);
-                self.add_hex(&g.background);
-                self.add_hex(&g.text);
-                self.add_hex(&g.indicator);
-            }
-            &None => (),
+        if let Some(g) = group {
+            self.add_hex(&g.border);
+            self.add_hex(&g.background);
+            self.add_hex(&g.text);
+            self.add_hex(&g.indicator);
         }
     }
 }
 
 impl Theme {
+    /// Layers `overlay` on top of this theme. Colors the overlay leaves as `None` are kept.
+    pub fn merge(&mut self, overlay: Theme) {
+        if let Some(overlay) = overlay.window_colors {
+            self.ensure_window_colors();
+            let window_colors = self.window_colors.as_mut().unwrap();
+            merge_group(&mut window_colors.focused, overlay.focused);
+            merge_group(&mut window_colors.focused_inactive, overlay.focused_inactive);
+            merge_group(&mut window_colors.unfocused, overlay.unfocused);
+            merge_group(&mut window_colors.urgent, overlay.urgent);
+        }
+
+        if let Some(overlay) = overlay.bar_colors {
+            self.ensure_bar_colors();
+            let bar_colors = self.bar_colors.as_mut().unwrap();
+            merge_color(&mut bar_colors.separator, overlay.separator);
+            merge_color(&mut bar_colors.background, overlay.background);
+            merge_color(&mut bar_colors.statusline, overlay.statusline);
+            merge_group(&mut bar_colors.focused_workspace, overlay.focused_workspace);
+            merge_group(&mut bar_colors.active_workspace, overlay.active_workspace);
+            merge_group(&mut bar_colors.inactive_workspace, overlay.inactive_workspace);
+            merge_group(&mut bar_colors.urgent_workspace, overlay.urgent_workspace);
+        }
+
+        if let Some(overlay) = overlay.status_colors {
+            let status_colors = self.status_colors.get_or_insert_with(StatusColors::empty);
+            merge_color(&mut status_colors.good, overlay.good);
+            merge_color(&mut status_colors.degraded, overlay.degraded);
+            merge_color(&mut status_colors.bad, overlay.bad);
+            merge_color(&mut status_colors.separator, overlay.separator);
+        }
+
+        if let Some(overlay) = overlay.font {
+            let font = self.font.get_or_insert_with(Font::default);
+            merge_color(&mut font.window, overlay.window);
+            merge_color(&mut font.bar, overlay.bar);
+        }
+        if let Some(overlay) = overlay.borders {
+            let borders = self.borders.get_or_insert_with(Borders::default);
+            merge_color(&mut borders.default_border, overlay.default_border);
+            merge_color(&mut borders.default_floating_border, overlay.default_floating_border);
+            merge_color(&mut borders.hide_edge_borders, overlay.hide_edge_borders);
+        }
+        if let Some(overlay) = overlay.gaps {
+            let gaps = self.gaps.get_or_insert_with(Gaps::default);
+            if overlay.inner.is_some() {
+                gaps.inner = overlay.inner;
+            }
+            if overlay.outer.is_some() {
+                gaps.outer = overlay.outer;
+            }
+        }
+    }
+
+    /// Overrides a single color by its dotted path in the theme file, e.g.
+    /// `window_colors.urgent.border`, `bar_colors.separator` or `status_colors.bad`.
+    pub fn set(&mut self, key: &str, value: &str) -> Result<(), String> {
+        let value = Some(colors::eval_color(value)?);
+        let path: Vec<&str> = key.split('.').collect();
+
+        // check the whole key before adding any sections to the theme
+        match path.as_slice() {
+            ["window_colors", group, _] if !WINDOW_GROUPS.contains(group) => {
+                return Err(format!("unknown window color group: {}", group));
+            }
+            ["bar_colors", part] if !BAR_COLORS.contains(part) => {
+                return Err(format!("unknown bar color: {}", part));
+            }
+            ["bar_colors", group, _] if !BAR_GROUPS.contains(group) => {
+                return Err(format!("unknown bar color group: {}", group));
+            }
+            [_, _, part] if !PARTS.contains(part) => {
+                return Err(format!("unknown color part: {}", part));
+            }
+            _ => {}
+        }
+
+        let group = match path.as_slice() {
+            ["window_colors", group, _] => {
+                self.ensure_window_colors();
+                let window_colors = self.window_colors.as_mut().unwrap();
+                match *group {
+                    "focused" => &mut window_colors.focused,
+                    "focused_inactive" => &mut window_colors.focused_inactive,
+                    "unfocused" => &mut window_colors.unfocused,
+                    "urgent" => &mut window_colors.urgent,
+                    _ => return Err(format!("unknown window color group: {}", group)),
+                }
+            }
+            ["bar_colors", part] => {
+                self.ensure_bar_colors();
+                let bar_colors = self.bar_colors.as_mut().unwrap();
+                match *part {
+                    "separator" => bar_colors.separator = value,
+                    "background" => bar_colors.background = value,
+                    "statusline" => bar_colors.statusline = value,
+                    _ => return Err(format!("unknown bar color: {}", part)),
+                }
+                return Ok(());
+            }
+            ["status_colors", part] => {
+                let status_colors = self.status_colors.get_or_insert_with(StatusColors::empty);
+                match *part {
+                    "good" => status_colors.good = value,
+                    "degraded" => status_colors.degraded = value,
+                    "bad" => status_colors.bad = value,
+                    "separator" => status_colors.separator = value,
+                    _ => return Err(format!("unknown status color: {}", part)),
+                }
+                return Ok(());
+            }
+            ["bar_colors", group, _] => {
+                self.ensure_bar_colors();
+                let bar_colors = self.bar_colors.as_mut().unwrap();
+                match *group {
+                    "focused_workspace" => &mut bar_colors.focused_workspace,
+                    "active_workspace" => &mut bar_colors.active_workspace,
+                    "inactive_workspace" => &mut bar_colors.inactive_workspace,
+                    "urgent_workspace" => &mut bar_colors.urgent_workspace,
+                    _ => return Err(format!("unknown bar color group: {}", group)),
+                }
+            }
+            _ => return Err(format!("unknown color: {}", key)),
+        };
+
+        group
+            .get_or_insert_with(ColorGroup::empty)
+            .set_part(path[2], value);
+        Ok(())
+    }
+
     fn ensure_window_colors(&mut self) {
         if self.window_colors.is_none() {
             self.window_colors = Some(WindowColors {
@@ -235,271 +669,119 @@ impl Theme {
         }
     }
 
-    pub fn to_yaml_with_colors(self) -> Yaml {
+    fn color_map(&self) -> ColorMap {
         let mut colormap = ColorMap::new();
 
-        let ref bar_colors = self.bar_colors;
-        match bar_colors {
-            &Some(ref bc) => {
-                colormap.add_hex(&bc.separator);
-                colormap.add_hex(&bc.background);
-                colormap.add_hex(&bc.statusline);
-                colormap.add_color_group(&bc.focused_workspace);
-                colormap.add_color_group(&bc.active_workspace);
-                colormap.add_color_group(&bc.inactive_workspace);
-                colormap.add_color_group(&bc.urgent_workspace);
-            }
-            &None => (),
-        }
-        let ref window_colors = self.window_colors;
-        match window_colors {
-            &Some(ref wc) => {
-                colormap.add_color_group(&wc.focused);
-                colormap.add_color_group(&wc.focused_inactive);
-                colormap.add_color_group(&wc.unfocused);
-                colormap.add_color_group(&wc.urgent);
-            }
-            &None => (),
-        }
-
-        let mut toplevel_yaml: LinkedHashMap<Yaml, Yaml> = LinkedHashMap::new();
-        let mut colormap_yaml: LinkedHashMap<Yaml, Yaml> = LinkedHashMap::new();
-        let mut metamap_yaml: LinkedHashMap<Yaml, Yaml> = LinkedHashMap::new();
-        let mut window_colors_yaml: LinkedHashMap<Yaml, Yaml> = LinkedHashMap::new();
-        let mut bar_colors_yaml: LinkedHashMap<Yaml, Yaml> = LinkedHashMap::new();
-
-        metamap_yaml.insert(
-            Yaml::String("description".to_string()),
-            Yaml::String(self.description.unwrap()),
-        );
-
-        for (key, value) in &colormap.colors {
-            colormap_yaml.insert(
-                Yaml::String(key.to_string()),
-                Yaml::String(value.to_string()),
-            );
-        }
-
-        match window_colors {
-            &Some(ref wc) => {
-                if wc.focused.is_some() {
-                    window_colors_yaml.insert(
-                        Yaml::String("focused".to_string()),
-                        wc.focused.as_ref().unwrap().to_yaml(&colormap),
-                    );
-                }
-                if wc.focused_inactive.is_some() {
-                    window_colors_yaml.insert(
-                        Yaml::String("focused_inactive".to_string()),
-                        wc.focused_inactive.as_ref().unwrap().to_yaml(&colormap),
-                    );
-                }
-                if wc.unfocused.is_some() {
-                    window_colors_yaml.insert(
-                        Yaml::String("unfocused".to_string()),
-                        wc.unfocused.as_ref().unwrap().to_yaml(&colormap),
-                    );
-                }
-                if wc.urgent.is_some() {
-                    window_colors_yaml.insert(
-                        Yaml::String("urgent".to_string()),
-                        wc.urgent.as_ref().unwrap().to_yaml(&colormap),
-                    );
-                }
-            }
-            &None => (),
-        }
-
-        match bar_colors {
-            &Some(ref bc) => {
-                if bc.background.is_some() {
-                    bar_colors_yaml.insert(
-                        Yaml::String("background".to_string()),
-                        Yaml::String(
-                            colormap.get_color(&bc.background.as_ref().unwrap().to_string()),
-                        ),
-                    );
-                }
-                if bc.statusline.is_some() {
-                    bar_colors_yaml.insert(
-                        Yaml::String("statusline".to_string()),
-                        Yaml::String(
-                            colormap.get_color(&bc.statusline.as_ref().unwrap().to_string()),
-                        ),
-                    );
-                }
-                if bc.separator.is_some() {
-                    bar_colors_yaml.insert(
-                        Yaml::String("separator".to_string()),
-                        Yaml::String(
-                            colormap.get_color(&bc.separator.as_ref().unwrap().to_string()),
-                        ),
-                    );
-                }
-                if bc.focused_workspace.is_some() {
-                    bar_colors_yaml.insert(
-                        Yaml::String("focused_workspace".to_string()),
-                        bc.focused_workspace.as_ref().unwrap().to_yaml(&colormap),
-                    );
-                }
-                if bc.active_workspace.is_some() {
-                    bar_colors_yaml.insert(
-                        Yaml::String("active_workspace".to_string()),
-                        bc.active_workspace.as_ref().unwrap().to_yaml(&colormap),
-                    );
-                }
-                if bc.urgent_workspace.is_some() {
-                    bar_colors_yaml.insert(
-                        Yaml::String("urgent_workspace".to_string()),
-                        bc.urgent_workspace.as_ref().unwrap().to_yaml(&colormap),
-                    );
-                }
-            }
-            &None => (),
+        if let Some(ref bc) = self.bar_colors {
+            colormap.add_hex(&bc.separator);
+            colormap.add_hex(&bc.background);
+            colormap.add_hex(&bc.statusline);
+            colormap.add_color_group(&bc.focused_workspace);
+            colormap.add_color_group(&bc.active_workspace);
+            colormap.add_color_group(&bc.inactive_workspace);
+            colormap.add_color_group(&bc.urgent_workspace);
+        }
+        if let Some(ref wc) = self.window_colors {
+            colormap.add_color_group(&wc.focused);
+            colormap.add_color_group(&wc.focused_inactive);
+            colormap.add_color_group(&wc.unfocused);
+            colormap.add_color_group(&wc.urgent);
+        }
+        if let Some(ref sc) = self.status_colors {
+            colormap.add_hex(&sc.good);
+            colormap.add_hex(&sc.degraded);
+            colormap.add_hex(&sc.bad);
+            colormap.add_hex(&sc.separator);
         }
 
-        toplevel_yaml.insert(Yaml::String("meta".to_string()), Yaml::Hash(metamap_yaml));
-        toplevel_yaml.insert(
-            Yaml::String("colors".to_string()),
-            Yaml::Hash(colormap_yaml),
-        );
-        toplevel_yaml.insert(
-            Yaml::String("window_colors".to_string()),
-            Yaml::Hash(window_colors_yaml),
-        );
-        toplevel_yaml.insert(
-            Yaml::String("bar_colors".to_string()),
-            Yaml::Hash(bar_colors_yaml),
-        );
-
-        Yaml::Hash(toplevel_yaml)
+        colormap
     }
-}
 
-fn parse_color(doc: &Yaml, color_spec: &Yaml) -> Option<String> {
-    if color_spec.as_str().is_none() {
-        return Option::None;
+    // Every color of the theme with the name it gets in the `colors` of an
+    // extracted theme.
+    pub fn named_colors(&self) -> Vec<(String, String)> {
+        self.color_map().colors.into_iter().collect()
     }
 
-    let color_spec = color_spec.as_str().unwrap();
-    let colors = &doc["colors"];
-
-    match colors[color_spec].as_str() {
-        Some(color) => Option::from(color.to_string()),
-        None => Option::from(color_spec.to_string()),
-    }
 }
 
-fn parse_color_group(doc: &Yaml, top_key: String, bottom_key: String) -> Option<ColorGroup> {
-    let top_key = top_key.as_str();
-    let bottom_key = bottom_key.as_str();
-
-    if doc[top_key][bottom_key].as_hash().is_none() {
-        return Option::None;
+fn resolve_color(palette: &mut Palette, key: &str, color: &mut Option<String>) -> Result<(), String> {
+    if let Some(spec) = color.take() {
+        let resolved = palette.eval_spec(&spec).map_err(|e| format!("{}: {}: {}", key, spec, e))?;
+        *color = Some(resolved);
     }
+    Ok(())
+}
 
-    let group_hash = &doc[top_key][bottom_key];
-
-    let mut group = ColorGroup {
-        border: Option::None,
-        background: Option::None,
-        text: Option::None,
-        indicator: Option::None,
-    };
-
-    for &part in vec!["border", "background", "text", "indicator"].iter() {
-        group.set_part(part, parse_color(&doc, &group_hash[part]));
+fn resolve_group(palette: &mut Palette, key: &str, group: &mut Option<ColorGroup>) -> Result<(), String> {
+    if let Some(ref mut group) = *group {
+        resolve_color(palette, &format!("{}.border", key), &mut group.border)?;
+        resolve_color(palette, &format!("{}.background", key), &mut group.background)?;
+        resolve_color(palette, &format!("{}.text", key), &mut group.text)?;
+        resolve_color(palette, &format!("{}.indicator", key), &mut group.indicator)?;
     }
-
-    Option::from(group)
+    Ok(())
 }
 
-fn parse_window_colors(doc: &Yaml) -> Option<WindowColors> {
-    if doc["window_colors"].as_hash().is_none() {
-        return Option::None;
+// A border style like `pixel 2`.
+fn check_border(key: &str, border: &Option<String>) -> Result<(), String> {
+    if let Some(ref border) = *border {
+        let words: Vec<&str> = border.split_whitespace().collect();
+        let valid = match words.as_slice() {
+            [style] => BORDER_STYLES.contains(style),
+            [style, width] => *style != "none" && BORDER_STYLES.contains(style) && width.parse::<u32>().is_ok(),
+            _ => false,
+        };
+        if !valid {
+            return Err(format!("borders.{} must be normal, none or pixel with an optional width, not {}", key, border));
+        }
     }
-
-    Option::from(WindowColors {
-        focused: parse_color_group(doc, "window_colors".to_string(), "focused".to_string()),
-        focused_inactive: parse_color_group(
-            doc,
-            "window_colors".to_string(),
-            "focused_inactive".to_string(),
-        ),
-        unfocused: parse_color_group(doc, "window_colors".to_string(), "unfocused".to_string()),
-        urgent: parse_color_group(doc, "window_colors".to_string(), "urgent".to_string()),
-    })
+    Ok(())
 }
 
-fn parse_bar_colors(doc: &Yaml) -> Option<BarColors> {
-    let bar_colors = &doc["bar_colors"];
-
-    if bar_colors.as_hash().is_none() {
-        return Option::None;
-    }
-
-    Option::from(BarColors {
-        separator: parse_color(&doc, &bar_colors["separator"]),
-        background: parse_color(&doc, &bar_colors["background"]),
-        statusline: parse_color(&doc, &bar_colors["statusline"]),
-        focused_workspace: parse_color_group(
-            doc,
-            "bar_colors".to_string(),
-            "focused_workspace".to_string(),
-        ),
-        active_workspace: parse_color_group(
-            doc,
-            "bar_colors".to_string(),
-            "active_workspace".to_string(),
-        ),
-        inactive_workspace: parse_color_group(
-            doc,
-            "bar_colors".to_string(),
-            "inactive_workspace".to_string(),
-        ),
-        urgent_workspace: parse_color_group(
-            doc,
-            "bar_colors".to_string(),
-            "urgent_workspace".to_string(),
-        ),
-    })
+fn check_borders(borders: &Borders) -> Result<(), String> {
+    check_border("default_border", &borders.default_border)?;
+    check_border("default_floating_border", &borders.default_floating_border)?;
+    if let Some(ref hide) = borders.hide_edge_borders {
+        if !HIDE_EDGE_BORDERS.contains(&hide.as_str()) {
+            return Err(format!(
+                "borders.hide_edge_borders must be one of {}, not {}",
+                HIDE_EDGE_BORDERS.join(", "),
+                hide
+            ));
+        }
+    }
+    Ok(())
 }
 
-pub fn from_yaml(doc: &Yaml) -> Theme {
-    let description = match doc["meta"]["description"].as_str() {
-        Some(d) => Option::from(String::from(d)),
-        None => Option::None,
-    };
-
-    Theme {
-        description: description,
-        window_colors: parse_window_colors(doc),
-        bar_colors: parse_bar_colors(doc),
-    }
+// Reads a theme document of any format.
+pub fn from_yaml(doc: &Yaml) -> Result<Theme, String> {
+    format::from_doc::<ThemeFile>(doc).and_then(Theme::try_from)
 }
 
-fn from_config_reader(input: BufReader<File>) -> Theme {
+// Extracts the theme of the config read from `input`.
+pub fn from_config<R: BufRead>(input: R) -> io::Result<Theme> {
     let mut theme = Theme {
-        description: Some("AUTOMATICALLY GENERATED THEME".to_string()),
+        meta: Meta {
+            description: Some("AUTOMATICALLY GENERATED THEME".to_string()),
+            ..Meta::default()
+        },
         window_colors: None,
         bar_colors: None,
+        status_colors: None,
+        font: None,
+        borders: None,
+        gaps: None,
     };
 
     let mut in_bar = false;
     let mut in_colors = false;
 
-    for line in input.lines() {
-        let line = line.unwrap();
-        let line = line.trim();
-        let mut vec: Vec<&str> = Vec::new();
-
-        for word in line.split(' ') {
-            if word != "" {
-                vec.push(word);
-            }
-        }
+    for (number, line) in input.lines().enumerate() {
+        let line = line?;
+        let vec: Vec<&str> = line.split_whitespace().collect();
 
-        if vec.len() == 0 || vec[0].starts_with("#") {
+        if vec.is_empty() || vec[0].starts_with('#') {
             continue;
         }
 
@@ -517,140 +799,104 @@ fn from_config_reader(input: BufReader<File>) -> Theme {
             continue;
         }
 
+        let value = || Some(vec[1..].join(" "));
+        match (in_bar, vec[0]) {
+            (true, "font") if !in_colors => {
+                theme.font.get_or_insert_with(Font::default).bar = value();
+            }
+            (false, "font") => theme.font.get_or_insert_with(Font::default).window = value(),
+            (false, "default_border") | (false, "new_window") => {
+                theme.borders.get_or_insert_with(Borders::default).default_border = value();
+            }
+            (false, "default_floating_border") | (false, "new_float") => {
+                theme.borders.get_or_insert_with(Borders::default).default_floating_border = value();
+            }
+            (false, "hide_edge_borders") => {
+                theme.borders.get_or_insert_with(Borders::default).hide_edge_borders = value();
+            }
+            (false, "gaps") if vec.len() == 3 => {
+                let gap = vec[2].parse().ok();
+                let gaps = theme.gaps.get_or_insert_with(Gaps::default);
+                match vec[1] {
+                    "inner" => gaps.inner = gap,
+                    "outer" => gaps.outer = gap,
+                    _ => (),
+                }
+            }
+            _ => (),
+        }
+
+        let colors = &vec[1..];
+        let check_count = |min: usize, max: usize| {
+            if colors.len() < min || colors.len() > max {
+                let expected = if min == max {
+                    min.to_string()
+                } else {
+                    format!("{} to {}", min, max)
+                };
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("line {}: `{}` takes {} colors, got {}", number + 1, vec[0], expected, colors.len()),
+                ));
+            }
+            Ok(())
+        };
+
         if in_colors {
             match vec[0] {
-                "separator" => {
-                    theme.ensure_bar_colors();
-                    let mut bar_colors = theme.bar_colors.unwrap();
-                    bar_colors.separator = Some(vec[1].to_string());
-                    theme.bar_colors = Some(bar_colors);
-                }
-                "background" => {
-                    theme.ensure_bar_colors();
-                    let mut bar_colors = theme.bar_colors.unwrap();
-                    bar_colors.background = Some(vec[1].to_string());
-                    theme.bar_colors = Some(bar_colors);
-                }
-                "statusline" => {
-                    theme.ensure_bar_colors();
-                    let mut bar_colors = theme.bar_colors.unwrap();
-                    bar_colors.statusline = Some(vec[1].to_string());
-                    theme.bar_colors = Some(bar_colors);
-                }
-                "focused_workspace" => {
-                    theme.ensure_bar_colors();
-                    let mut bar_colors = theme.bar_colors.unwrap();
-                    let mut group = bar_colors.focused_workspace.unwrap_or(ColorGroup::empty());
-                    group.border = Some(vec[1].to_string());
-                    group.background = Some(vec[2].to_string());
-                    if vec.get(3).is_some() {
-                        group.text = Some(vec[3].to_string());
+                "separator" | "background" | "statusline" => {
+                    check_count(1, 1)?;
+                    let color = Some(colors[0].to_string());
+                    let bar_colors = theme.bar_colors.get_or_insert_with(BarColors::default);
+                    match vec[0] {
+                        "separator" => bar_colors.separator = color,
+                        "background" => bar_colors.background = color,
+                        _ => bar_colors.statusline = color,
                     }
-                    bar_colors.focused_workspace = Some(group);
-                    theme.bar_colors = Some(bar_colors);
                 }
-                "inactive_workspace" => {
-                    theme.ensure_bar_colors();
-                    let mut bar_colors = theme.bar_colors.unwrap();
-                    let mut group = bar_colors.inactive_workspace.unwrap_or(ColorGroup::empty());
-                    group.border = Some(vec[1].to_string());
-                    group.background = Some(vec[2].to_string());
-                    if vec.get(3).is_some() {
-                        group.text = Some(vec[3].to_string());
+                "focused_workspace" | "active_workspace" | "inactive_workspace" | "urgent_workspace" => {
+                    check_count(2, 3)?;
+                    let bar_colors = theme.bar_colors.get_or_insert_with(BarColors::default);
+                    let group = match vec[0] {
+                        "focused_workspace" => &mut bar_colors.focused_workspace,
+                        "active_workspace" => &mut bar_colors.active_workspace,
+                        "inactive_workspace" => &mut bar_colors.inactive_workspace,
+                        _ => &mut bar_colors.urgent_workspace,
+                    };
+                    let group = group.get_or_insert_with(ColorGroup::empty);
+                    for (part, color) in PARTS.iter().zip(colors) {
+                        group.set_part(part, Some(color.to_string()));
                     }
-                    bar_colors.inactive_workspace = Some(group);
-                    theme.bar_colors = Some(bar_colors);
-                }
-                "urgent_workspace" => {
-                    theme.ensure_bar_colors();
-                    let mut bar_colors = theme.bar_colors.unwrap();
-                    let mut group = bar_colors.urgent_workspace.unwrap_or(ColorGroup::empty());
-                    group.border = Some(vec[1].to_string());
-                    group.background = Some(vec[2].to_string());
-                    if vec.get(3).is_some() {
-                        group.text = Some(vec[3].to_string());
-                    }
-                    bar_colors.urgent_workspace = Some(group);
-                    theme.bar_colors = Some(bar_colors);
                 }
                 _ => (),
-            };
+            }
         } else if !in_bar {
             match vec[0] {
-                "client.focused" => {
-                    theme.ensure_window_colors();
-                    let mut window_colors = theme.window_colors.unwrap();
-                    let mut group = window_colors.focused.unwrap_or(ColorGroup::empty());
-
-                    group.border = Some(vec[1].to_string());
-                    group.background = Some(vec[2].to_string());
-                    group.text = Some(vec[3].to_string());
-                    if vec.get(4).is_some() {
-                        group.indicator = Some(vec[4].to_string());
-                    }
-
-                    window_colors.focused = Some(group);
-                    theme.window_colors = Some(window_colors);
-                }
-                "client.focused_inactive" => {
-                    theme.ensure_window_colors();
-                    let mut window_colors = theme.window_colors.unwrap();
-                    let mut group = window_colors
-                        .focused_inactive
-                        .unwrap_or(ColorGroup::empty());
-
-                    group.border = Some(vec[1].to_string());
-                    group.background = Some(vec[2].to_string());
-                    group.text = Some(vec[3].to_string());
-                    if vec.get(4).is_some() {
-                        group.indicator = Some(vec[4].to_string());
-                    }
-
-                    window_colors.focused_inactive = Some(group);
-                    theme.window_colors = Some(window_colors);
-                }
-                "client.unfocused" => {
-                    theme.ensure_window_colors();
-                    let mut window_colors = theme.window_colors.unwrap();
-                    let mut group = window_colors.unfocused.unwrap_or(ColorGroup::empty());
-
-                    group.border = Some(vec[1].to_string());
-                    group.background = Some(vec[2].to_string());
-                    group.text = Some(vec[3].to_string());
-                    if vec.get(4).is_some() {
-                        group.indicator = Some(vec[4].to_string());
-                    }
-
-                    window_colors.unfocused = Some(group);
-                    theme.window_colors = Some(window_colors);
-                }
-                "client.urgent" => {
-                    theme.ensure_window_colors();
-                    let mut window_colors = theme.window_colors.unwrap();
-                    let mut group = window_colors.urgent.unwrap_or(ColorGroup::empty());
-
-                    group.border = Some(vec[1].to_string());
-                    group.background = Some(vec[2].to_string());
-                    group.text = Some(vec[3].to_string());
-                    if vec.get(4).is_some() {
-                        group.indicator = Some(vec[4].to_string());
+                "client.focused" | "client.focused_inactive" | "client.unfocused" | "client.urgent" => {
+                    // the fifth color is the child border, which themes don't have
+                    check_count(3, 5)?;
+                    let window_colors = theme.window_colors.get_or_insert_with(WindowColors::default);
+                    let group = match vec[0] {
+                        "client.focused" => &mut window_colors.focused,
+                        "client.focused_inactive" => &mut window_colors.focused_inactive,
+                        "client.unfocused" => &mut window_colors.unfocused,
+                        _ => &mut window_colors.urgent,
+                    };
+                    let group = group.get_or_insert_with(ColorGroup::empty);
+                    for (part, color) in PARTS.iter().zip(colors) {
+                        group.set_part(part, Some(color.to_string()));
                     }
-
-                    window_colors.urgent = Some(group);
-                    theme.window_colors = Some(window_colors);
                 }
                 _ => (),
-            };
+            }
         }
     }
 
-    theme
+    Ok(theme)
 }
 
-pub fn from_config_file(input: &String) -> Theme {
-    let input_file = File::open(input).unwrap();
-    let reader = BufReader::new(input_file);
-    from_config_reader(reader)
+pub fn from_config_file(path: &Path) -> io::Result<Theme> {
+    from_config(BufReader::new(File::open(path)?))
 }
 
 #[cfg(test)]
@@ -663,9 +909,9 @@ mod tests {
     pub fn test_config_parsing() {
         let mut d = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
         d.push("test-resources/minimal-config");
-        let theme = from_config_file(&d.to_str().unwrap().to_string());
+        let theme = from_config_file(&d).unwrap();
 
-        assert_eq!(theme.description.unwrap(), "AUTOMATICALLY GENERATED THEME");
+        assert_eq!(theme.meta.description.unwrap(), "AUTOMATICALLY GENERATED THEME");
 
         let wc = theme.window_colors.unwrap();
 
@@ -693,4 +939,82 @@ mod tests {
         assert_eq!(group.text, Some("#fdf6e3".to_string()));
         assert_eq!(group.indicator, None);
     }
+
+    #[test]
+    pub fn test_config_parsing_errors() {
+        let config = "bar {\n  colors {\n    background\n  }\n}\n";
+        let err = from_config(config.as_bytes()).err().unwrap();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert_eq!(err.to_string(), "line 3: `background` takes 1 colors, got 0");
+
+        let err = from_config("client.focused #111111\n".as_bytes()).err().unwrap();
+        assert_eq!(err.to_string(), "line 1: `client.focused` takes 3 to 5 colors, got 1");
+
+        let config = "bar {\n  colors {\n    active_workspace #111111 #222222\n  }\n}\n";
+        let theme = from_config(config.as_bytes()).unwrap();
+        let group = theme.bar_colors.unwrap().active_workspace.unwrap();
+        assert_eq!(group.background, Some("#222222".to_string()));
+        assert_eq!(group.text, None);
+    }
+
+    #[test]
+    pub fn test_merge_and_set() {
+        let mut d = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        d.push("test-resources/minimal-config");
+        let mut theme = from_config_file(&d).unwrap();
+
+        let docs = yaml_rust::YamlLoader::load_from_str(
+            "window_colors:\n  urgent:\n    border: '#ff0000'\nbar_colors:\n  statusline: '#ffffff'\n",
+        )
+        .unwrap();
+        theme.merge(from_yaml(&docs[0]).unwrap());
+
+        let wc = theme.window_colors.as_ref().unwrap();
+        let group = wc.urgent.as_ref().unwrap();
+        assert_eq!(group.border, Some("#ff0000".to_string()));
+        assert_eq!(group.background, Some("#d33682".to_string()));
+        let group = wc.focused.as_ref().unwrap();
+        assert_eq!(group.border, Some("#859900".to_string()));
+
+        let bc = theme.bar_colors.as_ref().unwrap();
+        assert_eq!(bc.statusline, Some("#ffffff".to_string()));
+        assert_eq!(bc.separator, Some("#dc322f".to_string()));
+
+        theme.set("window_colors.focused.indicator", "#00ff00").unwrap();
+        theme.set("bar_colors.separator", "#0000ff").unwrap();
+        assert!(theme.set("window_colors.nope.border", "#000000").is_err());
+        assert!(theme.set("window_colors.focused.border", "nope").is_err());
+
+        let docs = yaml_rust::YamlLoader::load_from_str("colors: {}\n").unwrap();
+        let mut empty = from_yaml(&docs[0]).unwrap();
+        assert!(empty.set("window_colors.nope.border", "#000000").is_err());
+        assert!(empty.set("bar_colors.focused_workspace.nope", "#000000").is_err());
+        assert!(empty.window_colors.is_none() && empty.bar_colors.is_none());
+
+        let wc = theme.window_colors.unwrap();
+        assert_eq!(wc.focused.unwrap().indicator, Some("#00ff00".to_string()));
+        assert_eq!(theme.bar_colors.unwrap().separator, Some("#0000ff".to_string()));
+    }
+
+    #[test]
+    pub fn test_embedded_themes_parse() {
+        let mut d = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        d.push("themes");
+
+        for entry in std::fs::read_dir(d).unwrap() {
+            let path = entry.unwrap().path();
+            let mut contents = String::new();
+            File::open(&path)
+                .unwrap()
+                .read_to_string(&mut contents)
+                .unwrap();
+            let docs = yaml_rust::YamlLoader::load_from_str(&contents).unwrap();
+            let theme = match from_yaml(&docs[0]) {
+                Ok(t) => t,
+                Err(e) => panic!("{}: {}", path.display(), e),
+            };
+            assert!(theme.meta.description.is_some(), "{}", path.display());
+            assert!(theme.meta.variant.is_some(), "{}", path.display());
+        }
+    }
 }
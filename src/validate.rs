@@ -0,0 +1,463 @@
+use std::collections::HashMap;
+use std::env;
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+
+// The keywords the i3 user's guide and sway(5) document for the top level of
+// a config. Sway takes its runtime commands in the config as well, so they
+// are included. Anything else is only a warning, since newer versions of
+// either may add keywords.
+const KEYWORDS: &[&str] = &[
+    "allow_tearing",
+    "assign",
+    "bar",
+    "bindcode",
+    "bindgesture",
+    "bindswitch",
+    "bindsym",
+    "border",
+    "client.background",
+    "client.focused",
+    "client.focused_inactive",
+    "client.focused_tab_title",
+    "client.placeholder",
+    "client.unfocused",
+    "client.urgent",
+    "default_border",
+    "default_floating_border",
+    "default_orientation",
+    "disable_randr15",
+    "exec",
+    "exec_always",
+    "exit",
+    "fake-outputs",
+    "fake_outputs",
+    "floating",
+    "floating_maximum_size",
+    "floating_minimum_size",
+    "floating_modifier",
+    "focus",
+    "focus_follows_mouse",
+    "focus_on_window_activation",
+    "focus_wrapping",
+    "font",
+    "for_window",
+    "force_display_urgency_hint",
+    "force_focus_wrapping",
+    "force_xinerama",
+    "fullscreen",
+    "gaps",
+    "hide_edge_borders",
+    "include",
+    "inhibit_idle",
+    "input",
+    "ipc-kill-timeout",
+    "ipc-socket",
+    "ipc_kill_timeout",
+    "ipc_socket",
+    "kill",
+    "layout",
+    "mark",
+    "max_render_time",
+    "mode",
+    "mouse_warping",
+    "move",
+    "new_float",
+    "new_window",
+    "no_focus",
+    "nop",
+    "opacity",
+    "output",
+    "popup_during_fullscreen",
+    "primary_selection",
+    "reload",
+    "rename",
+    "resize",
+    "restart_state",
+    "scratchpad",
+    "seat",
+    "set",
+    "set_from_resource",
+    "shortcuts_inhibitor",
+    "show_marks",
+    "smart_borders",
+    "smart_gaps",
+    "split",
+    "splith",
+    "splitt",
+    "splitv",
+    "sticky",
+    "swap",
+    "swaybg_command",
+    "swaynag_command",
+    "tiling_drag",
+    "tiling_drag_threshold",
+    "title_align",
+    "title_format",
+    "title_window_icon",
+    "titlebar_border_thickness",
+    "titlebar_padding",
+    "unbindcode",
+    "unbindgesture",
+    "unbindswitch",
+    "unbindsym",
+    "unmark",
+    "urgent",
+    "workspace",
+    "workspace_auto_back_and_forth",
+    "workspace_layout",
+    "xwayland",
+];
+
+const BAR_KEYWORDS: &[&str] = &[
+    "binding_mode_indicator",
+    "bindcode",
+    "bindsym",
+    "colors",
+    "font",
+    "gaps",
+    "height",
+    "hidden_state",
+    "i3bar_command",
+    "icon_theme",
+    "id",
+    "mode",
+    "modifier",
+    "output",
+    "padding",
+    "pango_markup",
+    "position",
+    "separator_symbol",
+    "socket_path",
+    "status_command",
+    "status_edge_padding",
+    "status_padding",
+    "strip_workspace_name",
+    "strip_workspace_numbers",
+    "swaybar_command",
+    "tray_bindcode",
+    "tray_bindsym",
+    "tray_output",
+    "tray_padding",
+    "verbose",
+    "workspace_buttons",
+    "workspace_command",
+    "workspace_min_width",
+    "wrap_scroll",
+];
+
+const MODE_KEYWORDS: &[&str] = &["bindcode", "bindsym", "bindswitch", "bindgesture", "set"];
+
+// The bar colors with the least and most number of colors they take. The
+// text color of the workspace buttons is optional.
+const BAR_COLORS: &[(&str, usize, usize)] = &[
+    ("background", 1, 1),
+    ("statusline", 1, 1),
+    ("separator", 1, 1),
+    ("focused_background", 1, 1),
+    ("focused_statusline", 1, 1),
+    ("focused_separator", 1, 1),
+    ("focused_workspace", 2, 3),
+    ("active_workspace", 2, 3),
+    ("inactive_workspace", 2, 3),
+    ("urgent_workspace", 2, 3),
+    ("binding_mode", 2, 3),
+];
+
+#[derive(Clone, Copy, PartialEq)]
+enum Block {
+    Bar,
+    Colors,
+    Mode,
+    // blocks i3-style knows nothing about, like sway's `input {}`
+    Other,
+}
+
+fn is_hex_color(color: &str) -> bool {
+    color.starts_with('#')
+        && (color.len() == 7 || color.len() == 9)
+        && color[1..].chars().all(|c| c.is_ascii_hexdigit())
+}
+
+// The config with continued lines joined and the number of the line each
+// one started on.
+fn logical_lines(contents: &str) -> Vec<(usize, String)> {
+    let mut lines = Vec::new();
+    let mut current: Option<(usize, String)> = None;
+
+    for (i, line) in contents.lines().enumerate() {
+        let (number, mut text) = current.take().unwrap_or((i + 1, String::new()));
+        if let Some(stripped) = line.strip_suffix('\\') {
+            text.push_str(stripped);
+            current = Some((number, text));
+        } else {
+            text.push_str(line);
+            lines.push((number, text));
+        }
+    }
+    if let Some(line) = current {
+        lines.push(line);
+    }
+
+    lines
+}
+
+fn check_colors(
+    errors: &mut Vec<String>,
+    number: usize,
+    key: &str,
+    args: &[&str],
+    min: usize,
+    max: usize,
+    variables: &HashMap<String, String>,
+) {
+    if args.len() < min || args.len() > max {
+        let expected = if min == max {
+            min.to_string()
+        } else {
+            format!("{} to {}", min, max)
+        };
+        errors.push(format!(
+            "line {}: `{}` takes {} colors, got {}",
+            number,
+            key,
+            expected,
+            args.len()
+        ));
+        return;
+    }
+
+    for arg in args {
+        let color = match variables.get(*arg) {
+            Some(value) => value.as_str(),
+            None if arg.starts_with('$') => {
+                errors.push(format!("line {}: undefined variable `{}`", number, arg));
+                continue;
+            }
+            None => arg,
+        };
+        if !is_hex_color(color) {
+            errors.push(format!("line {}: invalid color `{}`", number, color));
+        }
+    }
+}
+
+// What `check_config` found. Errors are in the parts of the config that
+// i3-style reads and writes. Everything else is only a warning, since i3 and
+// sway know keywords that aren't in the lists above.
+#[derive(Debug, Default, PartialEq)]
+pub struct Report {
+    pub errors: Vec<String>,
+    pub warnings: Vec<String>,
+}
+
+// Checks the parts of a config that i3-style reads and writes. It's no
+// replacement for `i3 -C`, but good enough when i3 isn't around.
+pub fn check_config(contents: &str) -> Report {
+    let mut errors = Vec::new();
+    let mut warnings = Vec::new();
+    let mut blocks: Vec<(Block, usize)> = Vec::new();
+    let mut variables: HashMap<String, String> = HashMap::new();
+
+    for (number, line) in logical_lines(contents) {
+        let words: Vec<&str> = line.split_whitespace().collect();
+        if words.is_empty() || words[0].starts_with('#') {
+            continue;
+        }
+
+        let key = words[0];
+        let opens = words.len() > 1 && *words.last().unwrap() == "{";
+        let args = if opens {
+            &words[1..words.len() - 1]
+        } else {
+            &words[1..]
+        };
+
+        if key == "}" {
+            if blocks.pop().is_none() {
+                errors.push(format!("line {}: unexpected `}}`", number));
+            }
+            continue;
+        }
+
+        let block = blocks.last().map(|b| b.0);
+
+        if key == "set" || key == "set_from_resource" {
+            if args.is_empty() || !args[0].starts_with('$') {
+                warnings.push(format!("line {}: `{}` needs a variable name", number, key));
+            } else {
+                // the value of a resource is only known to the X server, so
+                // assume the fallback
+                let value = if key == "set" {
+                    args[1..].join(" ")
+                } else {
+                    args.get(2..).map(|a| a.join(" ")).unwrap_or_default()
+                };
+                variables.insert(args[0].to_string(), value);
+            }
+            continue;
+        }
+
+        match block {
+            None => {
+                if !KEYWORDS.contains(&key) {
+                    warnings.push(format!("line {}: unknown keyword `{}`", number, key));
+                } else if key == "client.background" {
+                    check_colors(&mut errors, number, key, args, 1, 1, &variables);
+                } else if key.starts_with("client.") {
+                    check_colors(&mut errors, number, key, args, 3, 5, &variables);
+                }
+            }
+            Some(Block::Bar) => {
+                if !BAR_KEYWORDS.contains(&key) {
+                    warnings.push(format!("line {}: unknown bar keyword `{}`", number, key));
+                }
+            }
+            Some(Block::Colors) => match BAR_COLORS.iter().find(|c| c.0 == key) {
+                Some(&(_, min, max)) => {
+                    check_colors(&mut errors, number, key, args, min, max, &variables)
+                }
+                None => warnings.push(format!("line {}: unknown bar color `{}`", number, key)),
+            },
+            Some(Block::Mode) => {
+                if !MODE_KEYWORDS.contains(&key) {
+                    warnings.push(format!("line {}: unknown mode keyword `{}`", number, key));
+                }
+            }
+            Some(Block::Other) => (),
+        }
+
+        if opens {
+            let opened = match (block, key) {
+                (None, "bar") => Block::Bar,
+                (None, "mode") => Block::Mode,
+                (Some(Block::Bar), "colors") => Block::Colors,
+                _ => Block::Other,
+            };
+            blocks.push((opened, number));
+        }
+    }
+
+    for (_, number) in blocks {
+        errors.push(format!("line {}: block is never closed", number));
+    }
+
+    Report { errors, warnings }
+}
+
+fn find_in_path(name: &str) -> Option<PathBuf> {
+    let paths = env::var_os("PATH")?;
+    env::split_paths(&paths)
+        .map(|dir| dir.join(name))
+        .find(|path| path.is_file())
+}
+
+// Sway sets SWAYSOCK for its clients; everything else is taken to be i3.
+fn detect_wm() -> &'static str {
+    if env::var_os("SWAYSOCK").is_some() {
+        "sway"
+    } else {
+        "i3"
+    }
+}
+
+// Validates a config with `i3 -C` or `sway -C` when the window manager is
+// installed and with `check_config` otherwise. `contents` is what is in the
+// config at `path`, which is `-` for a config read from stdin. Returns the
+// warnings of `check_config`.
+pub fn validate_config(path: &str, contents: &str) -> Result<Vec<String>, String> {
+    let wm = detect_wm();
+
+    if let Some(binary) = find_in_path(wm) {
+        let from_stdin = path == "-";
+        let path = if from_stdin { "/dev/stdin" } else { path };
+        let mut child = Command::new(binary)
+            .arg("-C")
+            .arg("-c")
+            .arg(path)
+            .stdin(if from_stdin { Stdio::piped() } else { Stdio::null() })
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .map_err(|e| format!("Could not run `{} -C -c {}`: {}", wm, path, e))?;
+        if let Some(mut stdin) = child.stdin.take() {
+            // when this fails the exit status says why
+            let _ = stdin.write_all(contents.as_bytes());
+        }
+        let status = child
+            .wait()
+            .map_err(|e| format!("Could not run `{} -C -c {}`: {}", wm, path, e))?;
+
+        if !status.success() {
+            return Err(format!(
+                "Use `{} -C -c {}` to see validation errors.",
+                wm, path
+            ));
+        }
+        return Ok(Vec::new());
+    }
+
+    let report = check_config(contents);
+    let located = |problems: Vec<String>| -> Vec<String> {
+        problems.into_iter().map(|p| format!("{}: {}", path, p)).collect()
+    };
+    if report.errors.is_empty() {
+        Ok(located(report.warnings))
+    } else {
+        Err(located(report.errors).join("\n"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::fs;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_check_config() {
+        let mut default_config = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        default_config.push("test-resources/default-config");
+        let contents = fs::read_to_string(default_config).unwrap();
+        assert_eq!(check_config(&contents), Report::default());
+        assert_eq!(check_config("title_window_icon on\ntiling_drag_threshold 5\n"), Report::default());
+
+        let contents = "set $red #ff0000
+font pango:monospace 8
+focused_follows_mouse no
+client.focused $red #222222 \\
+    #333333
+client.unfocused #111111 #22222 #333333
+client.urgent $blue #222222 #333333
+bar {
+  colors {
+    background #000000
+    focused_workspace #111111
+    inactive #111111 #222222 #333333
+  }
+  status_comand i3status
+  padding 2px
+";
+        let report = check_config(contents);
+        assert_eq!(
+            report.errors,
+            vec![
+                "line 6: invalid color `#22222`",
+                "line 7: undefined variable `$blue`",
+                "line 11: `focused_workspace` takes 2 to 3 colors, got 1",
+                "line 8: block is never closed",
+            ]
+        );
+        assert_eq!(
+            report.warnings,
+            vec![
+                "line 3: unknown keyword `focused_follows_mouse`",
+                "line 12: unknown bar color `inactive`",
+                "line 14: unknown bar keyword `status_comand`",
+            ]
+        );
+        assert!(check_config("fake-outputs 1024x768+0+0\nipc-kill-timeout 100\n").warnings.is_empty());
+    }
+}
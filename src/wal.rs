@@ -0,0 +1,173 @@
+extern crate linked_hash_map;
+extern crate serde_json;
+extern crate yaml_rust;
+
+use self::linked_hash_map::LinkedHashMap;
+use self::serde_json::Value;
+use std::env;
+use std::path::{Path, PathBuf};
+use yaml_rust::{Yaml, YamlLoader};
+
+// Which colors of the scheme are used for what. The names in here are the
+// terminal colors pywal writes and Xresources defines: `background`,
+// `foreground`, `cursor` and `color0` to `color15`. A file in the same form
+// can be given to change the mapping.
+pub const DEFAULT_ROLES: &str = "
+window_colors:
+  focused:
+    border: color4
+    background: color4
+    text: background
+    indicator: color12
+  focused_inactive:
+    border: color8
+    background: color8
+    text: foreground
+    indicator: color8
+  unfocused:
+    border: background
+    background: background
+    text: color7
+    indicator: background
+  urgent:
+    border: color1
+    background: color1
+    text: background
+    indicator: color1
+bar_colors:
+  background: background
+  statusline: foreground
+  separator: color8
+  focused_workspace:
+    border: color4
+    background: color4
+    text: background
+  active_workspace:
+    border: color8
+    background: color8
+    text: foreground
+  inactive_workspace:
+    border: background
+    background: background
+    text: color7
+  urgent_workspace:
+    border: color1
+    background: color1
+    text: background
+";
+
+// Where pywal and wallust leave the colors of the current wallpaper.
+pub fn default_path() -> Option<PathBuf> {
+    match env::var_os("XDG_CACHE_HOME") {
+        Some(ref cache) if Path::new(cache).is_absolute() => Some(PathBuf::from(cache)),
+        _ => env::var_os("HOME").map(|h| PathBuf::from(h).join(".cache")),
+    }
+    .map(|cache| cache.join("wal").join("colors.json"))
+}
+
+// The colors of a colors.json by their names, the special ones first.
+pub fn palette(contents: &str) -> Result<Vec<(String, String)>, String> {
+    let json: Value = serde_json::from_str(contents).map_err(|e| e.to_string())?;
+    let mut palette = Vec::new();
+
+    let special = json["special"]
+        .as_object()
+        .ok_or("`special` is missing")?;
+    for name in &["background", "foreground", "cursor"] {
+        if let Some(color) = special.get(*name).and_then(|c| c.as_str()) {
+            palette.push((name.to_string(), color.to_string()));
+        }
+    }
+
+    for i in 0..16 {
+        let name = format!("color{}", i);
+        match json["colors"][&name].as_str() {
+            Some(color) => palette.push((name, color.to_string())),
+            None => return Err(format!("`colors.{}` is missing", name)),
+        }
+    }
+
+    Ok(palette)
+}
+
+fn s(value: &str) -> Yaml {
+    Yaml::String(value.to_string())
+}
+
+// A theme from named colors and a role mapping. The colors go in front of
+// the `colors` of the mapping, so those can be derived from them.
+pub fn with_roles(palette: &[(String, String)], roles: &str, description: &str) -> Result<Yaml, String> {
+    let mut roles = YamlLoader::load_from_str(roles)
+        .map_err(|e| format!("could not parse the role mapping: {}", e))?;
+    let mut theme = match roles.pop() {
+        Some(Yaml::Hash(h)) => h,
+        _ => return Err("the role mapping is not a map".to_string()),
+    };
+
+    let mut colors = LinkedHashMap::new();
+    for (name, color) in palette {
+        colors.insert(s(name), s(color));
+    }
+    if let Some(Yaml::Hash(own)) = theme.remove(&s("colors")) {
+        colors.extend(own);
+    }
+
+    let mut doc = LinkedHashMap::new();
+    if !theme.contains_key(&s("meta")) {
+        let mut meta = LinkedHashMap::new();
+        meta.insert(s("description"), s(description));
+        doc.insert(s("meta"), Yaml::Hash(meta));
+    }
+    doc.insert(s("colors"), Yaml::Hash(colors));
+    doc.extend(theme);
+    Ok(Yaml::Hash(doc))
+}
+
+pub fn to_theme_doc(contents: &str, roles: &str) -> Result<Yaml, String> {
+    with_roles(&palette(contents)?, roles, "Colors from pywal")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use theme::from_yaml;
+
+    const COLORS: &str = r##"{
+    "wallpaper": "/home/user/wallpaper.jpg",
+    "alpha": "100",
+    "special": {
+        "background": "#0f1419",
+        "foreground": "#c3c6c8",
+        "cursor": "#c3c6c8"
+    },
+    "colors": {
+        "color0": "#0f1419", "color1": "#8a4a4f", "color2": "#4f6a5a", "color3": "#7a6e4f",
+        "color4": "#4a5e8a", "color5": "#6a4f7a", "color6": "#4f7a7a", "color7": "#c3c6c8",
+        "color8": "#555a5e", "color9": "#8a4a4f", "color10": "#4f6a5a", "color11": "#7a6e4f",
+        "color12": "#4a5e8a", "color13": "#6a4f7a", "color14": "#4f7a7a", "color15": "#c3c6c8"
+    }
+}"##;
+
+    #[test]
+    fn test_wal() {
+        let theme = from_yaml(&to_theme_doc(COLORS, DEFAULT_ROLES).unwrap()).unwrap();
+        let focused = theme.window_colors.unwrap().focused.unwrap();
+        assert_eq!(focused.background, Some("#4a5e8a".to_string()));
+        assert_eq!(focused.text, Some("#0f1419".to_string()));
+        assert_eq!(theme.bar_colors.unwrap().statusline, Some("#c3c6c8".to_string()));
+
+        let roles = "
+colors:
+  accent: lighten(color2, 10%)
+bar_colors:
+  background: accent
+";
+        let theme = from_yaml(&to_theme_doc(COLORS, roles).unwrap()).unwrap();
+        assert!(theme.window_colors.is_none());
+        assert_ne!(theme.bar_colors.unwrap().background, Some("#4f6a5a".to_string()));
+
+        let err = to_theme_doc("{\"special\": {}, \"colors\": {}}", DEFAULT_ROLES).unwrap_err();
+        assert_eq!(err, "`colors.color0` is missing");
+    }
+}
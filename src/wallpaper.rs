@@ -0,0 +1,269 @@
+extern crate image;
+extern crate palette;
+
+use self::palette::{FromColor, Oklab, Oklch, Srgb};
+use std::path::Path;
+
+use lint;
+use theme::{BarColors, ColorGroup, Meta, Theme, Variant, WindowColors};
+
+// Images are scaled down to at most this many pixels on each side before
+// clustering, which is plenty to find the main colors.
+const SAMPLE_SIZE: u32 = 128;
+const ITERATIONS: usize = 20;
+// Clusters smaller than this share of the image don't get the accent role.
+const MIN_ACCENT_WEIGHT: f32 = 0.02;
+
+#[derive(Clone, Copy, Debug)]
+pub struct Cluster {
+    pub color: Oklab,
+    pub weight: f32,
+}
+
+fn distance(a: &Oklab, b: &Oklab) -> f32 {
+    (a.l - b.l).powi(2) + (a.a - b.a).powi(2) + (a.b - b.b).powi(2)
+}
+
+fn mean(colors: &[Oklab]) -> Oklab {
+    let n = colors.len() as f32;
+    let sum = colors.iter().fold((0.0, 0.0, 0.0), |s, c| (s.0 + c.l, s.1 + c.a, s.2 + c.b));
+    Oklab::new(sum.0 / n, sum.1 / n, sum.2 / n)
+}
+
+fn nearest(centers: &[Oklab], color: &Oklab) -> usize {
+    let mut best = 0;
+    for (i, center) in centers.iter().enumerate() {
+        if distance(center, color) < distance(&centers[best], color) {
+            best = i;
+        }
+    }
+    best
+}
+
+// k-means in Oklab, so that the clusters are colors that look alike. The
+// centers start out as far apart as possible, which keeps the result
+// deterministic and small but striking colors from being swallowed.
+pub fn clusters(samples: &[Oklab], k: usize) -> Vec<Cluster> {
+    if samples.is_empty() || k == 0 {
+        return Vec::new();
+    }
+
+    let mut centers = vec![samples[nearest(samples, &mean(samples))]];
+    while centers.len() < k {
+        let farthest = samples
+            .iter()
+            .map(|s| distance(&centers[nearest(&centers, s)], s))
+            .enumerate()
+            .fold((0, 0.0), |best, (i, d)| if d > best.1 { (i, d) } else { best });
+        if farthest.1 == 0.0 {
+            break;
+        }
+        centers.push(samples[farthest.0]);
+    }
+
+    let mut members: Vec<Vec<Oklab>> = Vec::new();
+    for _ in 0..ITERATIONS {
+        members = vec![Vec::new(); centers.len()];
+        for sample in samples {
+            members[nearest(&centers, sample)].push(*sample);
+        }
+        members.retain(|m| !m.is_empty());
+        centers = members.iter().map(|m| mean(m)).collect();
+    }
+
+    members
+        .iter()
+        .zip(centers)
+        .map(|(m, color)| Cluster {
+            color,
+            weight: m.len() as f32 / samples.len() as f32,
+        })
+        .collect()
+}
+
+fn hex(color: &Oklab) -> String {
+    let rgb: Srgb<u8> = Srgb::from_color(*color).into_format();
+    format!("#{:02x}{:02x}{:02x}", rgb.red, rgb.green, rgb.blue)
+}
+
+fn chroma(color: &Oklab) -> f32 {
+    (color.a.powi(2) + color.b.powi(2)).sqrt()
+}
+
+fn lighten(color: &Oklab, amount: f32) -> Oklab {
+    Oklab::new((color.l + amount).min(1.0), color.a, color.b)
+}
+
+// The color of `candidates` that is easiest to read on `background`.
+fn readable_on(background: &str, candidates: &[&String]) -> String {
+    let contrast = |c: &String| lint::contrast(c, background).unwrap_or(0.0);
+    candidates
+        .iter()
+        .max_by(|a, b| contrast(a).partial_cmp(&contrast(b)).unwrap())
+        .map(|c| (*c).clone())
+        .unwrap()
+}
+
+fn group(border: &str, background: &str, text: &str, indicator: &str) -> Option<ColorGroup> {
+    Some(ColorGroup {
+        border: Some(border.to_string()),
+        background: Some(background.to_string()),
+        text: Some(text.to_string()),
+        indicator: Some(indicator.to_string()),
+    })
+}
+
+fn workspace(border: &str, background: &str, text: &str) -> Option<ColorGroup> {
+    Some(ColorGroup {
+        border: Some(border.to_string()),
+        background: Some(background.to_string()),
+        text: Some(text.to_string()),
+        indicator: None,
+    })
+}
+
+// Gives the colors roles by their saturation and lightness: the most
+// saturated one marks focus and a reddish one urgency. Of the others, the
+// darkest is the background of the bar and unfocused windows and the
+// lightest is for text.
+fn theme_from_clusters(clusters: &[Cluster]) -> Theme {
+    let mut by_lightness: Vec<Cluster> = clusters.to_vec();
+    by_lightness.sort_by(|a, b| a.color.l.partial_cmp(&b.color.l).unwrap());
+
+    let by_chroma = |a: &&Cluster, b: &&Cluster| {
+        chroma(&a.color).partial_cmp(&chroma(&b.color)).unwrap()
+    };
+    let accent = match by_lightness
+        .iter()
+        .filter(|c| c.weight >= MIN_ACCENT_WEIGHT)
+        .max_by(by_chroma)
+    {
+        Some(c) => c.color,
+        None => by_lightness.iter().max_by(by_chroma).unwrap().color,
+    };
+
+    // a red from the image if there is one, otherwise a red as bright as the accent
+    let red = Oklch::new(accent.l.max(0.5), 0.17, 29.0);
+    let urgent = by_lightness
+        .iter()
+        .map(|c| c.color)
+        .filter(|c| chroma(c) > 0.08 && distance(c, &accent) > 0.0)
+        .find(|c| {
+            let hue = Oklch::from_color(*c).hue.into_positive_degrees();
+            !(40.0..=350.0).contains(&hue)
+        })
+        .unwrap_or_else(|| Oklab::from_color(red));
+
+    // the rest are used for backgrounds and text
+    let mut rest: Vec<Oklab> = by_lightness
+        .iter()
+        .map(|c| c.color)
+        .filter(|c| distance(c, &accent) > 0.0 && distance(c, &urgent) > 0.0)
+        .collect();
+    if rest.is_empty() {
+        rest.push(by_lightness[0].color);
+    }
+    let n = rest.len();
+    let darkest = rest[0];
+    let dark = if n > 2 { rest[1] } else { lighten(&darkest, 0.08) };
+    let middle = if n > 2 { rest[n / 2] } else { lighten(&darkest, 0.35) };
+    let lightest = if n > 1 { rest[n - 1] } else { lighten(&darkest, 0.7) };
+
+    let (darkest, dark, middle, lightest) = (hex(&darkest), hex(&dark), hex(&middle), hex(&lightest));
+    let (accent_light, accent, urgent) = (hex(&lighten(&accent, 0.15)), hex(&accent), hex(&urgent));
+    let accent_text = readable_on(&accent, &[&darkest, &lightest]);
+    let urgent_text = readable_on(&urgent, &[&darkest, &lightest]);
+    let dark_text = readable_on(&darkest, &[&middle, &lightest]);
+
+    Theme {
+        meta: Meta {
+            variant: Some(if lint::contrast(&darkest, "#000000").unwrap_or(1.0) < 4.5 {
+                Variant::Dark
+            } else {
+                Variant::Light
+            }),
+            ..Default::default()
+        },
+        window_colors: Some(WindowColors {
+            focused: group(&accent, &accent, &accent_text, &accent_light),
+            focused_inactive: group(&dark, &dark, &lightest, &dark),
+            unfocused: group(&darkest, &darkest, &dark_text, &darkest),
+            urgent: group(&urgent, &urgent, &urgent_text, &urgent),
+        }),
+        bar_colors: Some(BarColors {
+            separator: Some(middle.clone()),
+            background: Some(darkest.clone()),
+            statusline: Some(lightest.clone()),
+            focused_workspace: workspace(&accent, &accent, &accent_text),
+            active_workspace: workspace(&dark, &dark, &lightest),
+            inactive_workspace: workspace(&darkest, &darkest, &dark_text),
+            urgent_workspace: workspace(&urgent, &urgent, &urgent_text),
+        }),
+        status_colors: None,
+        font: None,
+        borders: None,
+        gaps: None,
+    }
+}
+
+pub fn theme_from_image(path: &Path, k: usize) -> Result<Theme, String> {
+    if k == 0 {
+        return Err("the theme needs at least one color".to_string());
+    }
+    let image = image::open(path).map_err(|e| e.to_string())?;
+    let image = image.thumbnail(SAMPLE_SIZE, SAMPLE_SIZE).to_rgba8();
+
+    let samples: Vec<Oklab> = image
+        .pixels()
+        .filter(|p| p[3] > 0)
+        .map(|p| Oklab::from_color(Srgb::new(p[0], p[1], p[2]).into_format::<f32>()))
+        .collect();
+    if samples.is_empty() {
+        return Err("the image has no opaque pixels".to_string());
+    }
+
+    let mut theme = theme_from_clusters(&clusters(&samples, k));
+    theme.meta.description = path
+        .file_name()
+        .map(|f| format!("Generated from {}", f.to_string_lossy()));
+    Ok(theme)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    extern crate tempfile;
+
+    use self::image::{Rgb, RgbImage};
+    use self::tempfile::tempdir;
+
+    #[test]
+    fn test_theme_from_image() {
+        // mostly dark blue with a light gray, a strong green and a bit of red
+        let image = RgbImage::from_fn(100, 100, |x, y| match (x, y) {
+            (0..=59, _) => Rgb([0x10, 0x18, 0x30]),
+            (60..=79, _) => Rgb([0xd0, 0xd0, 0xd0]),
+            (_, 0..=94) => Rgb([0x20, 0xc0, 0x40]),
+            _ => Rgb([0xc0, 0x20, 0x20]),
+        });
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("wallpaper.png");
+        image.save(&path).unwrap();
+
+        let theme = theme_from_image(&path, 4).unwrap();
+        assert!(theme_from_image(&path, 0).is_err());
+        assert_eq!(theme.meta.description, Some("Generated from wallpaper.png".to_string()));
+        assert_eq!(theme.meta.variant, Some(Variant::Dark));
+
+        let bc = theme.bar_colors.unwrap();
+        assert_eq!(bc.background, Some("#101830".to_string()));
+        assert_eq!(bc.statusline, Some("#d0d0d0".to_string()));
+
+        let wc = theme.window_colors.unwrap();
+        let focused = wc.focused.unwrap();
+        assert_eq!(focused.border, Some("#20c040".to_string()));
+        assert_eq!(focused.text, Some("#101830".to_string()));
+        assert_eq!(wc.urgent.unwrap().background, Some("#c02020".to_string()));
+    }
+}
@@ -1,12 +1,10 @@
 use std::collections::HashSet;
-use std::fs::File;
+use std::io;
 use std::io::prelude::*;
-use std::io::{stdout, BufReader};
-use std::path::Path;
 
-use theme::Theme;
+use theme::{ColorGroup, Theme, BAR_GROUPS};
 
-fn leading_spaces(string: &String) -> String {
+fn leading_spaces(string: &str) -> String {
     let mut leading = String::new();
 
     for c in string.chars() {
@@ -20,84 +18,145 @@ fn leading_spaces(string: &String) -> String {
     leading
 }
 
-pub fn write_config(input: &String, output: Option<&String>, theme: &Theme) {
-    let input_file = File::open(input).unwrap();
-    let reader = BufReader::new(input_file);
-    write_config_from_reader(reader, output, &theme);
+// The settings of the theme that are a line of their own outside of any
+// block, by the words they start with.
+fn settings(theme: &Theme) -> Vec<(&'static str, String)> {
+    let mut settings = Vec::new();
+    if let Some(font) = theme.font.as_ref().and_then(|f| f.window.as_ref()) {
+        settings.push(("font", font.clone()));
+    }
+    if let Some(ref borders) = theme.borders {
+        let values = [
+            ("default_border", &borders.default_border),
+            ("default_floating_border", &borders.default_floating_border),
+            ("hide_edge_borders", &borders.hide_edge_borders),
+        ];
+        for (key, value) in values.iter() {
+            if let Some(ref value) = **value {
+                settings.push((*key, value.clone()));
+            }
+        }
+    }
+    if let Some(ref gaps) = theme.gaps {
+        for (key, value) in [("gaps inner", gaps.inner), ("gaps outer", gaps.outer)].iter() {
+            if let Some(value) = *value {
+                settings.push((*key, value.to_string()));
+            }
+        }
+    }
+    settings
 }
 
-pub fn write_config_from_reader(input: BufReader<File>, output: Option<&String>, theme: &Theme) {
-    let mut writer = match output {
-        Some(x) => {
-            let path = Path::new(x.as_str());
-            Box::new(File::create(&path).unwrap()) as Box<Write>
-        }
-        None => Box::new(stdout()) as Box<Write>,
+// Which of the settings a line sets. `new_window` and `new_float` are the
+// old names of the default borders.
+fn setting_of(words: &[&str]) -> &'static str {
+    match words {
+        ["font", ..] => "font",
+        ["default_border", ..] | ["new_window", ..] => "default_border",
+        ["default_floating_border", ..] | ["new_float", ..] => "default_floating_border",
+        ["hide_edge_borders", ..] => "hide_edge_borders",
+        ["gaps", "inner", _] => "gaps inner",
+        ["gaps", "outer", _] => "gaps outer",
+        _ => "",
+    }
+}
+
+// Writes a line like `client.focused #a #b #c #d` with the first `parts`
+// colors of the group in place of those on the line. Colors the group doesn't
+// have are kept, and the line is copied as it is when it ends up with fewer
+// than two colors.
+fn write_group_line<W: Write>(
+    writer: &mut W,
+    original_line: &str,
+    leading: &str,
+    words: &[&str],
+    group: Option<&ColorGroup>,
+    parts: usize,
+) -> io::Result<()> {
+    let group = match group {
+        Some(group) => group,
+        None => return writer.write_all(original_line.as_bytes()),
     };
 
+    let mut colors = Vec::new();
+    let theme_colors = [&group.border, &group.background, &group.text, &group.indicator];
+    for (i, color) in theme_colors.iter().take(parts).enumerate() {
+        // a color can't be written without the ones before it
+        match color.as_ref().map(String::as_str).or_else(|| words.get(i + 1).cloned()) {
+            Some(color) => colors.push(color),
+            None => break,
+        }
+    }
+
+    if colors.len() < 2 {
+        return writer.write_all(original_line.as_bytes());
+    }
+    writer.write_all(format!("{}{} {}\n", leading, words[0], colors.join(" ")).as_bytes())
+}
+
+// Writes the config read from `input` with the theme applied to `writer`.
+// Lines that have nothing to do with the theme are copied as they are.
+pub fn write_theme<R: BufRead, W: Write>(input: R, mut writer: W, theme: &Theme) -> io::Result<()> {
+
     let mut in_bar = false;
     let mut in_colors = false;
     let mut colors_found = false;
     let mut found_bar_colors = HashSet::new();
     let mut found_window_colors = HashSet::new();
+    let settings = settings(theme);
+    let mut found_settings = HashSet::new();
+    let bar_font = theme.font.as_ref().and_then(|f| f.bar.as_ref());
+    let mut bar_font_found = false;
 
     for line in input.lines() {
-        let original_line = line.unwrap() + "\n";
+        let original_line = line? + "\n";
         let leading = leading_spaces(&original_line);
         // TODO count leading spaces
         let line = original_line.trim();
-        let mut vec: Vec<&str> = Vec::new();
+        let vec: Vec<&str> = line.split(' ').filter(|w| !w.is_empty()).collect();
 
-        for word in line.split(' ') {
-            if word != "" {
-                vec.push(word);
-            }
-        }
-
-        if vec.len() > 0 && !vec[0].starts_with("#") {
-            if in_colors && vec[0] == "}" {
+        if !vec.is_empty() && !vec[0].starts_with('#') {
+            if in_colors && vec[0] == "}" && theme.bar_colors.is_none() {
+                in_colors = false;
+                writer.write_all(original_line.as_bytes())?;
+                continue;
+            } else if in_colors && vec[0] == "}" {
                 let bar_colors = &theme.bar_colors.as_ref().unwrap();
                 if !found_bar_colors.contains("separator") {
-                    bar_colors.separator.as_ref().map(|color| {
-                        writer.write(leading.as_bytes()).unwrap();
-                        writer.write(leading.as_bytes()).unwrap();
-                        writer.write(b"separator ").unwrap();
-                        writer.write(color.as_bytes()).unwrap();
-                        writer.write(b"\n").unwrap();
-                    });
+                    if let Some(color) = bar_colors.separator.as_ref() {
+                        writer.write_all(leading.as_bytes())?;
+                        writer.write_all(leading.as_bytes())?;
+                        writer.write_all(b"separator ")?;
+                        writer.write_all(color.as_bytes())?;
+                        writer.write_all(b"\n")?;
+                    }
                 }
 
                 if !found_bar_colors.contains("background") {
-                    bar_colors.background.as_ref().map(|color| {
-                        writer.write(leading.as_bytes()).unwrap();
-                        writer.write(leading.as_bytes()).unwrap();
-                        writer.write(b"background ").unwrap();
-                        writer.write(color.as_bytes()).unwrap();
-                        writer.write(b"\n").unwrap();
-                    });
+                    if let Some(color) = bar_colors.background.as_ref() {
+                        writer.write_all(leading.as_bytes())?;
+                        writer.write_all(leading.as_bytes())?;
+                        writer.write_all(b"background ")?;
+                        writer.write_all(color.as_bytes())?;
+                        writer.write_all(b"\n")?;
+                    }
                 }
 
                 if !found_bar_colors.contains("statusline") {
-                    bar_colors.statusline.as_ref().map(|color| {
-                        writer.write(leading.as_bytes()).unwrap();
-                        writer.write(leading.as_bytes()).unwrap();
-                        writer.write(b"statusline ").unwrap();
-                        writer.write(color.as_bytes()).unwrap();
-                        writer.write(b"\n").unwrap();
-                    });
+                    if let Some(color) = bar_colors.statusline.as_ref() {
+                        writer.write_all(leading.as_bytes())?;
+                        writer.write_all(leading.as_bytes())?;
+                        writer.write_all(b"statusline ")?;
+                        writer.write_all(color.as_bytes())?;
+                        writer.write_all(b"\n")?;
+                    }
                 }
 
-                let group_names = vec![
-                    "focused_workspace",
-                    "active_workspace",
-                    "inactive_workspace",
-                    "urgent_workspace",
-                ];
-                for group_name in &group_names {
-                    if found_bar_colors.contains(&group_name.to_string()) {
+                for group_name in BAR_GROUPS {
+                    if found_bar_colors.contains(*group_name) {
                         continue;
                     }
-                    let group = match group_name.as_ref() {
+                    let group = match *group_name {
                         "focused_workspace" => bar_colors.focused_workspace.as_ref(),
                         "active_workspace" => bar_colors.active_workspace.as_ref(),
                         "inactive_workspace" => bar_colors.inactive_workspace.as_ref(),
@@ -115,67 +174,51 @@ pub fn write_config_from_reader(input: BufReader<File>, output: Option<&String>,
                         continue;
                     }
 
-                    writer.write(leading.as_bytes()).unwrap();
-                    writer.write(leading.as_bytes()).unwrap();
-                    writer.write(group_name.as_bytes()).unwrap();
-                    writer.write(b" ").unwrap();
-                    writer
-                        .write(group.border.as_ref().unwrap().as_bytes())
-                        .unwrap();
-                    writer.write(b" ").unwrap();
-                    writer
-                        .write(group.background.as_ref().unwrap().as_bytes())
-                        .unwrap();
-                    writer.write(b" ").unwrap();
-                    writer
-                        .write(group.text.as_ref().unwrap().as_bytes())
-                        .unwrap();
-                    writer.write(b" ").unwrap();
-
-                    match group.indicator {
-                        Some(ref color) => {
-                            writer.write(b" ").unwrap();
-                            writer.write(color.as_bytes()).unwrap();
-                            ()
-                        }
-                        None => (),
-                    };
-
-                    writer.write(b"\n").unwrap();
+                    writer.write_all(leading.as_bytes())?;
+                    writer.write_all(leading.as_bytes())?;
+                    writer.write_all(group_name.as_bytes())?;
+                    writer.write_all(b" ")?;
+                    writer.write_all(group.border.as_ref().unwrap().as_bytes())?;
+                    writer.write_all(b" ")?;
+                    writer.write_all(group.background.as_ref().unwrap().as_bytes())?;
+                    writer.write_all(b" ")?;
+                    writer.write_all(group.text.as_ref().unwrap().as_bytes())?;
+                    writer.write_all(b"\n")?;
                 }
 
                 in_colors = false;
                 found_bar_colors.clear();
-                writer.write(original_line.as_bytes()).unwrap();
+                writer.write_all(original_line.as_bytes())?;
                 continue;
             } else if in_bar && vec[0] == "}" {
-                let bar_colors = &theme.bar_colors.as_ref().unwrap();
-                if !colors_found {
-                    writer.write(b"  colors {\n").unwrap();
-                    bar_colors.separator.as_ref().map(|color| {
-                        writer.write(b"    separator ").unwrap();
-                        writer.write(color.as_bytes()).unwrap();
-                        writer.write(b"\n").unwrap();
-                    });
-                    bar_colors.background.as_ref().map(|color| {
-                        writer.write(b"    background ").unwrap();
-                        writer.write(color.as_bytes()).unwrap();
-                        writer.write(b"\n").unwrap();
-                    });
-                    bar_colors.statusline.as_ref().map(|color| {
-                        writer.write(b"    statusline ").unwrap();
-                        writer.write(color.as_bytes()).unwrap();
-                        writer.write(b"\n").unwrap();
-                    });
-
-                    let group_names = vec![
-                        "focused_workspace",
-                        "active_workspace",
-                        "inactive_workspace",
-                        "urgent_workspace",
-                    ];
-                    for group_name in &group_names {
-                        let group = match group_name.as_ref() {
+                if let Some(font) = bar_font {
+                    if !bar_font_found {
+                        writer.write_all(format!("  font {}\n", font).as_bytes())?;
+                    }
+                }
+                bar_font_found = false;
+
+                // a theme without bar colors leaves the bar as it is
+                if let (false, Some(bar_colors)) = (colors_found, theme.bar_colors.as_ref()) {
+                    writer.write_all(b"  colors {\n")?;
+                    if let Some(color) = bar_colors.separator.as_ref() {
+                        writer.write_all(b"    separator ")?;
+                        writer.write_all(color.as_bytes())?;
+                        writer.write_all(b"\n")?;
+                    }
+                    if let Some(color) = bar_colors.background.as_ref() {
+                        writer.write_all(b"    background ")?;
+                        writer.write_all(color.as_bytes())?;
+                        writer.write_all(b"\n")?;
+                    }
+                    if let Some(color) = bar_colors.statusline.as_ref() {
+                        writer.write_all(b"    statusline ")?;
+                        writer.write_all(color.as_bytes())?;
+                        writer.write_all(b"\n")?;
+                    }
+
+                    for group_name in BAR_GROUPS {
+                        let group = match *group_name {
                             "focused_workspace" => bar_colors.focused_workspace.as_ref(),
                             "active_workspace" => bar_colors.active_workspace.as_ref(),
                             "inactive_workspace" => bar_colors.inactive_workspace.as_ref(),
@@ -195,76 +238,46 @@ pub fn write_config_from_reader(input: BufReader<File>, output: Option<&String>,
                             continue;
                         }
 
-                        writer.write(b"    ").unwrap();
-                        writer.write(group_name.as_bytes()).unwrap();
-                        writer.write(b" ").unwrap();
-                        writer
-                            .write(group.border.as_ref().unwrap().as_bytes())
-                            .unwrap();
-                        writer.write(b" ").unwrap();
-                        writer
-                            .write(group.background.as_ref().unwrap().as_bytes())
-                            .unwrap();
-                        writer.write(b" ").unwrap();
-                        writer
-                            .write(group.text.as_ref().unwrap().as_bytes())
-                            .unwrap();
-                        writer.write(b" ").unwrap();
-
-                        match group.indicator {
-                            Some(ref color) => {
-                                writer.write(b" ").unwrap();
-                                writer.write(color.as_bytes()).unwrap();
-                                ()
-                            }
-                            None => (),
-                        };
-
-                        writer.write(b"\n").unwrap();
+                        writer.write_all(b"    ")?;
+                        writer.write_all(group_name.as_bytes())?;
+                        writer.write_all(b" ")?;
+                        writer.write_all(group.border.as_ref().unwrap().as_bytes())?;
+                        writer.write_all(b" ")?;
+                        writer.write_all(group.background.as_ref().unwrap().as_bytes())?;
+                        writer.write_all(b" ")?;
+                        writer.write_all(group.text.as_ref().unwrap().as_bytes())?;
+                        writer.write_all(b"\n")?;
                     }
-                    writer.write(b"  }\n").unwrap();
+                    writer.write_all(b"  }\n")?;
                 }
 
                 colors_found = false;
                 in_bar = false;
-                writer.write(original_line.as_bytes()).unwrap();
+                writer.write_all(original_line.as_bytes())?;
                 continue;
             }
 
             if in_colors {
                 if theme.bar_colors.is_none() {
-                    writer.write(original_line.as_bytes()).unwrap();
+                    writer.write_all(original_line.as_bytes())?;
                     continue;
                 }
 
                 let bar_colors = &theme.bar_colors.as_ref().unwrap();
 
-                if vec!["separator", "background", "statusline"].contains(&vec[0]) {
+                if ["separator", "background", "statusline"].contains(&vec[0]) {
                     found_bar_colors.insert(vec[0].to_string());
-                    writer.write(leading.as_bytes()).unwrap();
-                    writer.write(vec[0].as_bytes()).unwrap();
-                    writer.write(b" ").unwrap();
-
-                    writer
-                        .write(match vec[0] {
-                            "separator" => match bar_colors.separator {
-                                Some(ref color) => color.as_bytes(),
-                                None => vec[1].as_bytes(),
-                            },
-                            "background" => match bar_colors.background {
-                                Some(ref color) => color.as_bytes(),
-                                None => vec[1].as_bytes(),
-                            },
-                            "statusline" => match bar_colors.statusline {
-                                Some(ref color) => color.as_bytes(),
-                                None => vec[1].as_bytes(),
-                            },
-                            _ => vec[1].as_bytes(),
-                        })
-                        .unwrap();
-                    writer.write(b"\n").unwrap();
+                    let color = match vec[0] {
+                        "separator" => bar_colors.separator.as_ref(),
+                        "background" => bar_colors.background.as_ref(),
+                        _ => bar_colors.statusline.as_ref(),
+                    };
+                    match color.map(String::as_str).or_else(|| vec.get(1).cloned()) {
+                        Some(color) => writer.write_all(format!("{}{} {}\n", leading, vec[0], color).as_bytes())?,
+                        None => writer.write_all(original_line.as_bytes())?,
+                    }
                     continue;
-                } else if vec![
+                } else if [
                     "focused_workspace",
                     "active_workspace",
                     "inactive_workspace",
@@ -277,55 +290,11 @@ pub fn write_config_from_reader(input: BufReader<File>, output: Option<&String>,
                         "focused_workspace" => bar_colors.focused_workspace.as_ref(),
                         "active_workspace" => bar_colors.active_workspace.as_ref(),
                         "inactive_workspace" => bar_colors.inactive_workspace.as_ref(),
-                        "urgent_workspace" => bar_colors.urgent_workspace.as_ref(),
-                        _ => panic!("not reached"),
+                        _ => bar_colors.urgent_workspace.as_ref(),
                     };
 
-                    if group.is_none() {
-                        writer.write(original_line.as_bytes()).unwrap();
-                        continue;
-                    }
-
-                    let group = group.unwrap();
-
-                    writer.write(leading.as_bytes()).unwrap();
-                    writer.write(vec[0].as_bytes()).unwrap();
-                    writer.write(b" ").unwrap();
-
-                    writer
-                        .write(match group.border.as_ref() {
-                            Some(color) => color.as_bytes(),
-                            None => vec[1].as_bytes(),
-                        })
-                        .unwrap();
-                    writer.write(b" ").unwrap();
-
-                    writer
-                        .write(match group.background.as_ref() {
-                            Some(color) => color.as_bytes(),
-                            None => vec[2].as_bytes(),
-                        })
-                        .unwrap();
-                    writer.write(b" ").unwrap();
-
-                    writer
-                        .write(match group.text.as_ref() {
-                            Some(color) => color.as_bytes(),
-                            None => vec[3].as_bytes(),
-                        })
-                        .unwrap();
-
-                    if vec.get(3).is_some() || group.indicator.is_some() {
-                        writer.write(b" ").unwrap();
-                        writer
-                            .write(match group.indicator.as_ref() {
-                                Some(color) => color.as_bytes(),
-                                None => vec[3].as_bytes(),
-                            })
-                            .unwrap();
-                    }
-                    writer.write(b"\n").unwrap();
-
+                    // the bar has no indicator color
+                    write_group_line(&mut writer, &original_line, &leading, &vec, group, 3)?;
                     continue;
                 }
                 continue;
@@ -333,17 +302,33 @@ pub fn write_config_from_reader(input: BufReader<File>, output: Option<&String>,
 
             if vec[0] == "bar" {
                 in_bar = true;
-                writer.write(original_line.as_bytes()).unwrap();
+                writer.write_all(original_line.as_bytes())?;
                 continue;
             }
             if in_bar && vec[0] == "colors" {
                 in_colors = true;
                 colors_found = true;
-                writer.write(original_line.as_bytes()).unwrap();
+                writer.write_all(original_line.as_bytes())?;
                 continue;
             }
+            if in_bar && vec[0] == "font" {
+                if let Some(font) = bar_font {
+                    bar_font_found = true;
+                    writer.write_all(format!("{}font {}\n", leading, font).as_bytes())?;
+                    continue;
+                }
+            }
 
-            if vec![
+            let setting = setting_of(&vec);
+            if !in_bar && !setting.is_empty() {
+                if let Some((key, value)) = settings.iter().find(|s| s.0 == setting) {
+                    found_settings.insert(setting);
+                    writer.write_all(format!("{}{} {}\n", leading, key, value).as_bytes())?;
+                    continue;
+                }
+            }
+
+            if [
                 "client.focused",
                 "client.unfocused",
                 "client.focused_inactive",
@@ -353,7 +338,7 @@ pub fn write_config_from_reader(input: BufReader<File>, output: Option<&String>,
             {
                 found_window_colors.insert(vec[0].to_string());
                 if theme.window_colors.is_none() {
-                    writer.write(original_line.as_bytes()).unwrap();
+                    writer.write_all(original_line.as_bytes())?;
                     continue;
                 }
 
@@ -363,78 +348,39 @@ pub fn write_config_from_reader(input: BufReader<File>, output: Option<&String>,
                     "client.focused" => window_colors.focused.as_ref(),
                     "client.unfocused" => window_colors.unfocused.as_ref(),
                     "client.focused_inactive" => window_colors.focused_inactive.as_ref(),
-                    "client.urgent" => window_colors.urgent.as_ref(),
-                    _ => panic!("not reached"),
+                    _ => window_colors.urgent.as_ref(),
                 };
 
-                if group.is_none() {
-                    writer.write(original_line.as_bytes()).unwrap();
-                    continue;
-                }
-
-                let group = group.unwrap();
-
-                writer.write(leading.as_bytes()).unwrap();
-                writer.write(vec[0].as_bytes()).unwrap();
-                writer.write(b" ").unwrap();
-
-                writer
-                    .write(match group.border.as_ref() {
-                        Some(color) => color.as_bytes(),
-                        None => vec[1].as_bytes(),
-                    })
-                    .unwrap();
-                writer.write(b" ").unwrap();
-
-                writer
-                    .write(match group.background.as_ref() {
-                        Some(color) => color.as_bytes(),
-                        None => vec[2].as_bytes(),
-                    })
-                    .unwrap();
-                writer.write(b" ").unwrap();
-
-                if vec.get(3).is_some() || group.text.is_some() {
-                    writer
-                        .write(match group.text.as_ref() {
-                            Some(color) => color.as_bytes(),
-                            None => vec[3].as_bytes(),
-                        })
-                        .unwrap();
-                    writer.write(b" ").unwrap();
-
-                    // cant write the indicator in the text field
-                    if vec.get(4).is_some() || group.indicator.is_some() {
-                        writer
-                            .write(match group.indicator.as_ref() {
-                                Some(color) => color.as_bytes(),
-                                None => vec[4].as_bytes(),
-                            })
-                            .unwrap();
-                    }
-                }
-
-                writer.write(b"\n").unwrap();
+                write_group_line(&mut writer, &original_line, &leading, &vec, group, 4)?;
                 continue;
             }
         }
 
-        writer.write(original_line.as_bytes()).unwrap();
+        writer.write_all(original_line.as_bytes())?;
+    }
+
+    for (key, value) in &settings {
+        if !found_settings.contains(key) {
+            writer.write_all(format!("{} {}\n", key, value).as_bytes())?;
+        }
     }
 
-    let window_color_names = vec![
+    let window_color_names = [
         "client.focused",
         "client.focused_inactive",
         "client.unfocused",
         "client.urgent",
     ];
     for window_color_name in &window_color_names {
-        if found_window_colors.contains(&window_color_name.to_string()) {
+        if found_window_colors.contains(*window_color_name) {
             continue;
         }
-        let window_colors = &theme.window_colors.as_ref().unwrap();
+        let window_colors = match theme.window_colors.as_ref() {
+            Some(w) => w,
+            None => break,
+        };
 
-        let group = match window_color_name.as_ref() {
+        let group = match *window_color_name {
             "client.focused" => window_colors.focused.as_ref(),
             "client.unfocused" => window_colors.unfocused.as_ref(),
             "client.focused_inactive" => window_colors.focused_inactive.as_ref(),
@@ -448,38 +394,37 @@ pub fn write_config_from_reader(input: BufReader<File>, output: Option<&String>,
 
         let group = group.unwrap();
 
-        writer.write(window_color_name.as_bytes()).unwrap();
-        writer.write(b" ").unwrap();
+        writer.write_all(window_color_name.as_bytes())?;
+        writer.write_all(b" ")?;
         writer
-            .write(match group.border.as_ref() {
+            .write_all(match group.border.as_ref() {
                 Some(color) => color.as_bytes(),
                 None => b"#000000",
-            })
-            .unwrap();
-        writer.write(b" ").unwrap();
+            })?;
+        writer.write_all(b" ")?;
 
         writer
-            .write(match group.background.as_ref() {
+            .write_all(match group.background.as_ref() {
                 Some(color) => color.as_bytes(),
                 None => b"#000000",
-            })
-            .unwrap();
-        writer.write(b" ").unwrap();
+            })?;
+        writer.write_all(b" ")?;
 
         writer
-            .write(match group.text.as_ref() {
+            .write_all(match group.text.as_ref() {
                 Some(color) => color.as_bytes(),
                 None => b"#000000",
-            })
-            .unwrap();
+            })?;
 
-        group.indicator.as_ref().map(|color| {
-            writer.write(b" ").unwrap();
-            writer.write(color.as_bytes()).unwrap();
-        });
+        if let Some(color) = group.indicator.as_ref() {
+            writer.write_all(b" ")?;
+            writer.write_all(color.as_bytes())?;
+        }
 
-        writer.write(b"\n").unwrap();
+        writer.write_all(b"\n")?;
     }
+
+    Ok(())
 }
 
 #[cfg(test)]
@@ -487,25 +432,23 @@ mod tests {
     use super::*;
     use theme::from_yaml;
 
-    extern crate tempfile;
     extern crate yaml_rust;
 
-    use self::tempfile::tempdir;
+    use std::fs;
     use std::path::PathBuf;
     use yaml_rust::YamlLoader;
 
-    fn get_file_contents(path: &String) -> String {
-        let mut file = File::open(path).expect("could not open file");
-        let mut contents = String::new();
-        file.read_to_string(&mut contents).unwrap();
-        contents
-    }
-
     fn get_resource_contents(path: &str) -> String {
         let mut d = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
         d.push("test-resources");
         d.push(path);
-        get_file_contents(&d.to_str().unwrap().to_string())
+        fs::read_to_string(d).expect("could not open file")
+    }
+
+    fn write_to_string(config: &str, theme: &Theme) -> String {
+        let mut out = Vec::new();
+        write_theme(config.as_bytes(), &mut out, theme).unwrap();
+        String::from_utf8(out).unwrap()
     }
 
     #[test]
@@ -516,34 +459,117 @@ mod tests {
             YamlLoader::load_from_str(contents.as_str()).expect("Could not parse yaml for theme");
         let doc = &docs[0];
 
-        let theme = from_yaml(&doc);
-
-        let dir = tempdir().unwrap();
-        let output_path = dir
-            .path()
-            .join("writer-test-output")
-            .to_str()
-            .unwrap()
-            .to_string();
+        let theme = from_yaml(doc).unwrap();
 
         let configs = vec![
-            vec!["test-resources/minimal-config", "minimal-config-expected"],
-            vec!["test-resources/missing-config", "missing-config-expected"],
+            vec!["minimal-config", "minimal-config-expected"],
+            vec!["missing-config", "missing-config-expected"],
         ];
 
         for config in &configs {
-            let mut d = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
-            d.push(&config[0]);
-            let input_path = d.to_str().unwrap().to_string();
-
-            write_config(&input_path, Some(&output_path), &theme);
-
-            let contents = get_file_contents(&output_path);
-            let expected_contents = get_resource_contents(&config[1]);
+            let contents = write_to_string(&get_resource_contents(config[0]), &theme);
+            let expected_contents = get_resource_contents(config[1]);
 
             println!("'{}'", contents);
             println!("'{}'", expected_contents);
             assert_eq!(contents, expected_contents);
         }
     }
+
+    #[test]
+    fn test_settings() {
+        let contents = get_resource_contents("test-theme.yaml");
+        let docs = YamlLoader::load_from_str(&contents).unwrap();
+        let mut theme = from_yaml(&docs[0]).unwrap();
+
+        let docs = YamlLoader::load_from_str(
+            "font:
+  window: pango:DejaVu Sans Mono 10
+  bar: pango:DejaVu Sans Mono 9
+borders:
+  default_border: 2
+  hide_edge_borders: smart
+gaps:
+  inner: 8
+",
+        )
+        .unwrap();
+        theme.merge(from_yaml(&docs[0]).unwrap());
+
+        let config = "font pango:monospace 8
+new_window pixel 1
+gaps inner 4
+gaps outer 2
+bar {
+  status_command i3status
+}
+";
+        let contents = write_to_string(config, &theme);
+        assert!(contents.starts_with(
+            "font pango:DejaVu Sans Mono 10
+default_border pixel 2
+gaps inner 8
+gaps outer 2
+bar {
+  status_command i3status
+  font pango:DejaVu Sans Mono 9
+"
+        ));
+        assert!(contents.contains("\nhide_edge_borders smart\n"));
+
+        // themes with only some of the sections leave the rest alone
+        let config = "client.focused #000000 #000000 #ffffff\nbar {\n  colors {\n    background #000000\n  }\n}\nbar {\n}\n";
+        for partial in &["font: pango:Terminus 9", "window_colors:\n  focused:\n    border: '#123456'\n    background: '#123456'\n    text: '#ffffff'"] {
+            let docs = YamlLoader::load_from_str(partial).unwrap();
+            let contents = write_to_string(config, &from_yaml(&docs[0]).unwrap());
+            assert!(contents.contains("  colors {\n    background #000000\n  }\n}\nbar {\n"), "{}", contents);
+        }
+    }
+
+    #[test]
+    fn test_bar_colors() {
+        let docs = YamlLoader::load_from_str(
+            "bar_colors:
+  background: '#000000'
+  focused_workspace:
+    border: '#111111'
+    background: '#222222'
+    indicator: '#444444'
+  urgent_workspace:
+    border: '#555555'
+    background: '#666666'
+    text: '#777777'
+    indicator: '#888888'
+",
+        )
+        .unwrap();
+        let theme = from_yaml(&docs[0]).unwrap();
+
+        // lines with fewer colors than usual are kept or filled in, but the
+        // indicator never makes it onto the bar
+        let config = "bar {
+  colors {
+    separator
+    background #ffffff
+    focused_workspace #aaaaaa #bbbbbb
+    active_workspace #aaaaaa #bbbbbb #cccccc
+  }
+}
+";
+        let contents = write_to_string(config, &theme);
+        assert_eq!(
+            contents,
+            "bar {
+  colors {
+    separator
+    background #000000
+    focused_workspace #111111 #222222
+    active_workspace #aaaaaa #bbbbbb #cccccc
+    urgent_workspace #555555 #666666 #777777
+  }
+}
+"
+        );
+        assert_eq!(write_to_string(&contents, &theme), contents);
+    }
 }
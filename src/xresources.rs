@@ -0,0 +1,275 @@
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+// How deep `#include`s can nest before we assume they include each other.
+const MAX_INCLUDE_DEPTH: usize = 16;
+
+pub fn default_path() -> Option<PathBuf> {
+    env::var_os("HOME").map(|h| PathBuf::from(h).join(".Xresources"))
+}
+
+fn is_identifier(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_'
+}
+
+// Replaces the macros in `line`, like cpp does for xrdb.
+fn expand(line: &str, defines: &HashMap<String, String>) -> String {
+    if defines.is_empty() {
+        return line.to_string();
+    }
+
+    let mut out = String::new();
+    let mut word = String::new();
+    for c in line.chars().chain(Some('\n')) {
+        if is_identifier(c) {
+            word.push(c);
+            continue;
+        }
+        match defines.get(&word) {
+            Some(value) => out.push_str(value),
+            None => out.push_str(&word),
+        }
+        word.clear();
+        out.push(c);
+    }
+    out.pop();
+    out
+}
+
+fn strip_comments(line: &str) -> &str {
+    let line = match line.find("/*") {
+        Some(i) => &line[..i],
+        None => line,
+    };
+    match line.find("//") {
+        Some(i) => &line[..i],
+        None => line,
+    }
+}
+
+struct Preprocessor {
+    defines: HashMap<String, String>,
+    // whether the lines are used, for every `#if` we are in
+    conditions: Vec<bool>,
+    resources: Vec<(String, String)>,
+}
+
+impl Preprocessor {
+    fn active(&self) -> bool {
+        self.conditions.iter().all(|c| *c)
+    }
+
+    fn directive(&mut self, line: &str, dir: &Path, depth: usize) -> Result<(), String> {
+        let line = line[1..].trim_start();
+        let mut parts = line.splitn(2, char::is_whitespace);
+        let name = parts.next().unwrap_or("");
+        let rest = parts.next().unwrap_or("").trim();
+
+        match name {
+            "ifdef" => self.conditions.push(self.defines.contains_key(rest)),
+            "ifndef" => self.conditions.push(!self.defines.contains_key(rest)),
+            "else" => match self.conditions.last_mut() {
+                Some(c) => *c = !*c,
+                None => return Err("#else without #if".to_string()),
+            },
+            "endif" if self.conditions.pop().is_none() => {
+                return Err("#endif without #if".to_string());
+            }
+            "endif" => {}
+            _ if !self.active() => {}
+            "define" => {
+                let mut parts = rest.splitn(2, char::is_whitespace);
+                let macro_name = parts.next().unwrap_or("");
+                if macro_name.is_empty() || !macro_name.chars().all(is_identifier) {
+                    return Err(format!("invalid #define: {}", rest));
+                }
+                let value = parts.next().unwrap_or("").trim();
+                let value = expand(value, &self.defines);
+                self.defines.insert(macro_name.to_string(), value);
+            }
+            "undef" => {
+                self.defines.remove(rest);
+            }
+            "include" => {
+                let file = rest.trim_matches(|c| c == '"' || c == '<' || c == '>');
+                self.file(&dir.join(file), depth + 1)?;
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn file(&mut self, path: &Path, depth: usize) -> Result<(), String> {
+        if depth > MAX_INCLUDE_DEPTH {
+            return Err(format!("{}: too many nested includes", path.display()));
+        }
+        let contents = fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        let dir = path.parent().unwrap_or_else(|| Path::new("."));
+        self.source(&contents, dir, depth)
+            .map_err(|e| format!("{}: {}", path.display(), e))
+    }
+
+    fn source(&mut self, contents: &str, dir: &Path, depth: usize) -> Result<(), String> {
+        let contents = contents.replace("\\\n", "");
+        for (i, line) in contents.lines().enumerate() {
+            let line = strip_comments(line).trim();
+            if line.starts_with('#') {
+                self.directive(line, dir, depth)
+                    .map_err(|e| format!("line {}: {}", i + 1, e))?;
+                continue;
+            }
+            if line.is_empty() || line.starts_with('!') || !self.active() {
+                continue;
+            }
+
+            let line = expand(line, &self.defines);
+            let mut parts = line.splitn(2, ':');
+            if let (Some(name), Some(value)) = (parts.next(), parts.next()) {
+                self.resources.push((name.trim().to_string(), value.trim().to_string()));
+            }
+        }
+        Ok(())
+    }
+}
+
+// The resources of an Xresources file with `#include`s, `#define`s and
+// `#ifdef`s resolved, in order. Includes are relative to `dir`.
+pub fn resources(contents: &str, dir: &Path) -> Result<Vec<(String, String)>, String> {
+    let mut preprocessor = Preprocessor {
+        defines: HashMap::new(),
+        conditions: Vec::new(),
+        resources: Vec::new(),
+    };
+    preprocessor.source(contents, dir, 0)?;
+    Ok(preprocessor.resources)
+}
+
+// X colors can also be given as `rgb:rr/gg/bb`. Anything else, including
+// components X doesn't allow, is returned as it is.
+fn to_hex(color: &str) -> String {
+    let parts: Vec<&str> = match color.strip_prefix("rgb:") {
+        Some(rgb) => rgb.split('/').collect(),
+        None => return color.to_string(),
+    };
+    let valid = |part: &&str| (1..=4).contains(&part.len()) && part.chars().all(|c| c.is_ascii_hexdigit());
+    if parts.len() != 3 || !parts.iter().all(valid) {
+        return color.to_string();
+    }
+    let mut hex = String::from("#");
+    for part in parts {
+        // scale 1 to 4 hex digits to 2
+        let value = u32::from_str_radix(part, 16).unwrap();
+        let max = (1u32 << (4 * part.len())) - 1;
+        hex.push_str(&format!("{:02x}", (value * 255 + max / 2) / max));
+    }
+    hex
+}
+
+fn palette_name(resource: &str) -> Option<String> {
+    match resource {
+        "background" | "foreground" => Some(resource.to_string()),
+        "cursorColor" => Some("cursor".to_string()),
+        _ => {
+            let n = resource.strip_prefix("color")?.parse::<u8>().ok()?;
+            if n < 16 {
+                Some(resource.to_string())
+            } else {
+                None
+            }
+        }
+    }
+}
+
+// The terminal colors and the `i3wm` resources by name. Colors for every
+// program (`*.color4`) take precedence over those for a single one
+// (`URxvt.color4`), and the `i3wm` resources over both.
+pub fn palette(resources: &[(String, String)]) -> Vec<(String, String)> {
+    let mut generic = HashMap::new();
+    let mut specific = HashMap::new();
+    let mut i3wm = Vec::new();
+
+    for (name, value) in resources {
+        let components: Vec<&str> = name.split(['.', '*']).collect();
+        let last = components.last().unwrap();
+        if components.len() == 2 && components[0] == "i3wm" {
+            i3wm.push((last.to_string(), to_hex(value)));
+        } else if let Some(palette_name) = palette_name(last) {
+            let any_program = components[..components.len() - 1].iter().all(|c| c.is_empty());
+            let colors = if any_program { &mut generic } else { &mut specific };
+            colors.insert(palette_name, to_hex(value));
+        }
+    }
+
+    let mut names = vec!["background".to_string(), "foreground".to_string(), "cursor".to_string()];
+    names.extend((0..16).map(|i| format!("color{}", i)));
+
+    let mut palette: Vec<(String, String)> = names
+        .into_iter()
+        .filter_map(|name| {
+            let color = generic.get(&name).or_else(|| specific.get(&name))?.clone();
+            Some((name, color))
+        })
+        .collect();
+    for (name, color) in i3wm {
+        palette.retain(|c| c.0 != name);
+        palette.push((name, color));
+    }
+    palette
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    extern crate tempfile;
+
+    use self::tempfile::tempdir;
+
+    #[test]
+    fn test_xresources() {
+        let dir = tempdir().unwrap();
+        fs::write(
+            dir.path().join("colors"),
+            "#define blue #268bd2\n#define base03 #002b36\n",
+        )
+        .unwrap();
+
+        let contents = "! my colors
+#include \"colors\"
+#define accent blue
+*.background: base03
+*foreground:  rgb:83/94/96
+URxvt.background: #000000
+URxvt*color1: #dc322f
+*.color4: accent /* comment */
+#ifdef HIDPI
+*.color5: #ffffff
+#else
+*.color5: \\
+    #6c71c4
+#endif
+i3wm.focused: accent
+";
+        let resources = resources(contents, dir.path()).unwrap();
+        assert_eq!(resources[0], ("*.background".to_string(), "#002b36".to_string()));
+
+        let palette = palette(&resources);
+        let color = |name: &str| palette.iter().find(|c| c.0 == name).map(|c| c.1.as_str());
+        assert_eq!(color("background"), Some("#002b36"));
+        assert_eq!(color("foreground"), Some("#839496"));
+        assert_eq!(color("color1"), Some("#dc322f"));
+        assert_eq!(color("color4"), Some("#268bd2"));
+        assert_eq!(color("color5"), Some("#6c71c4"));
+        assert_eq!(color("focused"), Some("#268bd2"));
+        assert_eq!(color("cursor"), None);
+
+        assert_eq!(to_hex("rgb:ffff/8/0"), "#ff8800");
+        assert_eq!(to_hex("rgb:fffffffff/0/0"), "rgb:fffffffff/0/0");
+        assert_eq!(to_hex("rgb:ff/00"), "rgb:ff/00");
+
+        let err = super::resources("#include \"missing\"\n", dir.path()).unwrap_err();
+        assert!(err.starts_with("line 1: "));
+    }
+}